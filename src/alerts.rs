@@ -7,7 +7,6 @@ use x11rb::protocol::xproto::{
 };
 use x11rb::COPY_DEPTH_FROM_PARENT;
 
-use crate::config::config::{BACKGROUND, BORDER_FOCUSED, FOREGROUND, MARGIN};
 use crate::wm::WindowManager;
 
 struct Rect {
@@ -54,8 +53,8 @@ impl WindowManager {
             WindowClass::INPUT_OUTPUT,
             0,
             &CreateWindowAux::new()
-                .background_pixel(BACKGROUND)
-                .border_pixel(BORDER_FOCUSED)
+                .background_pixel(self.config.appearance.background)
+                .border_pixel(self.config.appearance.border_focused)
                 .override_redirect(1)
                 .event_mask(EventMask::EXPOSURE),
         )?;
@@ -64,8 +63,8 @@ impl WindowManager {
             gc_id,
             alert_id,
             &CreateGCAux::new()
-                .foreground(FOREGROUND)
-                .background(BACKGROUND),
+                .foreground(self.config.appearance.foreground)
+                .background(self.config.appearance.background),
         )?;
 
         self.conn.map_window(alert_id)?;
@@ -126,18 +125,6 @@ impl WindowManager {
         Ok(())
     }
 
-    pub fn restack_alerts(&mut self) -> Result<()> {
-        for alert in &self.alerts {
-            self.conn.configure_window(
-                alert.window,
-                &x11rb::protocol::xproto::ConfigureWindowAux::new()
-                    .stack_mode(x11rb::protocol::xproto::StackMode::ABOVE),
-            )?;
-        }
-        self.conn.flush()?;
-        Ok(())
-    }
-
     pub fn redraw_alert(&self, alert: &Alert) -> Result<()> {
         self.conn
             .image_text8(alert.window, alert.gc, 20, 30, alert.message.as_bytes())?;
@@ -147,7 +134,7 @@ impl WindowManager {
 
     fn position(&self, pos: Position, win_w: u32, win_h: u32) -> Rect {
         let monitor = self.monitors.current();
-        let margin = MARGIN;
+        let margin = self.config.appearance.margin;
 
         let (relative_x, relative_y) = match pos {
             Position::TopLeft => (margin, margin),
@@ -11,60 +11,80 @@ pub struct Atoms {
     pub net_wm_state: Atom,
     pub net_wm_state_fullscreen: Atom,
     pub net_active_window: Atom,
+    pub utf8_string: Atom,
+    pub dxwm_socket: Atom,
+    pub net_supported: Atom,
+    pub net_supporting_wm_check: Atom,
+    pub net_wm_name: Atom,
+    pub net_number_of_desktops: Atom,
+    pub net_current_desktop: Atom,
+    pub net_desktop_names: Atom,
+    pub net_desktop_viewport: Atom,
+    pub net_client_list: Atom,
+    pub net_client_list_stacking: Atom,
+    pub net_wm_desktop: Atom,
+    pub net_close_window: Atom,
+    pub net_moveresize_window: Atom,
+    pub net_wm_window_type: Atom,
+    pub net_wm_window_type_dock: Atom,
+    pub net_wm_strut: Atom,
+    pub net_wm_strut_partial: Atom,
+    pub net_workarea: Atom,
+    pub net_wm_state_demands_attention: Atom,
+    pub net_wm_window_type_dialog: Atom,
+    pub net_wm_window_type_utility: Atom,
+    pub net_wm_window_type_splash: Atom,
+    pub net_wm_window_type_toolbar: Atom,
+    pub net_wm_window_type_desktop: Atom,
+    pub net_wm_state_above: Atom,
+    pub net_wm_state_below: Atom,
 }
 
 impl Atoms {
     pub fn new<C: Connection>(conn: &C) -> Result<Self> {
-        let wm_protocols = conn
-            .intern_atom(false, b"WM_PROTOCOLS")?
-            .reply()
-            .context("Failed to intern WM_PROTOCOLS")?
-            .atom;
-
-        let wm_delete_window = conn
-            .intern_atom(false, b"WM_DELETE_WINDOW")?
-            .reply()
-            .context("Failed to intern WM_DELETE_WINDOW")?
-            .atom;
-
-        let wm_state = conn
-            .intern_atom(false, b"WM_STATE")?
-            .reply()
-            .context("Failed to intern WM_STATE")?
-            .atom;
-
-        let wm_take_focus = conn
-            .intern_atom(false, b"WM_TAKE_FOCUS")?
-            .reply()
-            .context("Failed to intern WM_TAKE_FOCUS")?
-            .atom;
-
-        let net_wm_state = conn
-            .intern_atom(false, b"_NET_WM_STATE")?
-            .reply()
-            .context("Failed to intern _NET_WM_STATE")?
-            .atom;
-
-        let net_wm_state_fullscreen = conn
-            .intern_atom(false, b"_NET_WM_STATE_FULLSCREEN")?
-            .reply()
-            .context("Failed to intern _NET_WM_STATE_FULLSCREEN")?
-            .atom;
-
-        let net_active_window = conn
-            .intern_atom(false, b"_NET_ACTIVE_WINDOW")?
-            .reply()
-            .context("Failed to intern _NET_ACTIVE_WINDOW")?
-            .atom;
-
         Ok(Self {
-            wm_protocols,
-            wm_delete_window,
-            wm_state,
-            wm_take_focus,
-            net_wm_state,
-            net_wm_state_fullscreen,
-            net_active_window,
+            wm_protocols: intern(conn, "WM_PROTOCOLS")?,
+            wm_delete_window: intern(conn, "WM_DELETE_WINDOW")?,
+            wm_state: intern(conn, "WM_STATE")?,
+            wm_take_focus: intern(conn, "WM_TAKE_FOCUS")?,
+            net_wm_state: intern(conn, "_NET_WM_STATE")?,
+            net_wm_state_fullscreen: intern(conn, "_NET_WM_STATE_FULLSCREEN")?,
+            net_active_window: intern(conn, "_NET_ACTIVE_WINDOW")?,
+            utf8_string: intern(conn, "UTF8_STRING")?,
+            dxwm_socket: intern(conn, "_DXWM_SOCKET")?,
+            net_supported: intern(conn, "_NET_SUPPORTED")?,
+            net_supporting_wm_check: intern(conn, "_NET_SUPPORTING_WM_CHECK")?,
+            net_wm_name: intern(conn, "_NET_WM_NAME")?,
+            net_number_of_desktops: intern(conn, "_NET_NUMBER_OF_DESKTOPS")?,
+            net_current_desktop: intern(conn, "_NET_CURRENT_DESKTOP")?,
+            net_desktop_names: intern(conn, "_NET_DESKTOP_NAMES")?,
+            net_desktop_viewport: intern(conn, "_NET_DESKTOP_VIEWPORT")?,
+            net_client_list: intern(conn, "_NET_CLIENT_LIST")?,
+            net_client_list_stacking: intern(conn, "_NET_CLIENT_LIST_STACKING")?,
+            net_wm_desktop: intern(conn, "_NET_WM_DESKTOP")?,
+            net_close_window: intern(conn, "_NET_CLOSE_WINDOW")?,
+            net_moveresize_window: intern(conn, "_NET_MOVERESIZE_WINDOW")?,
+            net_wm_window_type: intern(conn, "_NET_WM_WINDOW_TYPE")?,
+            net_wm_window_type_dock: intern(conn, "_NET_WM_WINDOW_TYPE_DOCK")?,
+            net_wm_strut: intern(conn, "_NET_WM_STRUT")?,
+            net_wm_strut_partial: intern(conn, "_NET_WM_STRUT_PARTIAL")?,
+            net_workarea: intern(conn, "_NET_WORKAREA")?,
+            net_wm_state_demands_attention: intern(conn, "_NET_WM_STATE_DEMANDS_ATTENTION")?,
+            net_wm_window_type_dialog: intern(conn, "_NET_WM_WINDOW_TYPE_DIALOG")?,
+            net_wm_window_type_utility: intern(conn, "_NET_WM_WINDOW_TYPE_UTILITY")?,
+            net_wm_window_type_splash: intern(conn, "_NET_WM_WINDOW_TYPE_SPLASH")?,
+            net_wm_window_type_toolbar: intern(conn, "_NET_WM_WINDOW_TYPE_TOOLBAR")?,
+            net_wm_window_type_desktop: intern(conn, "_NET_WM_WINDOW_TYPE_DESKTOP")?,
+            net_wm_state_above: intern(conn, "_NET_WM_STATE_ABOVE")?,
+            net_wm_state_below: intern(conn, "_NET_WM_STATE_BELOW")?,
         })
     }
 }
+
+fn intern<C: Connection>(conn: &C, name: &str) -> Result<Atom> {
+    Ok(conn
+        .intern_atom(false, name.as_bytes())?
+        .reply()
+        .with_context(|| format!("Failed to intern {}", name))?
+        .atom)
+}
@@ -0,0 +1,168 @@
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+use x11rb::rust_connection::RustConnection;
+
+const USAGE: &str = "usage: dxwmc [--json] <command> [args...]
+
+examples:
+  dxwmc workspace 3
+  dxwmc layout next
+  dxwmc focus-monitor next
+  dxwmc bind Super+x spawn xterm
+  dxwmc query clients --json
+  dxwmc tree
+  dxwmc subscribe workspace focus";
+
+fn main() {
+    let mut json = false;
+    let mut args = env::args().skip(1).peekable();
+
+    // options only count before the command, everything after it is passed on
+    while let Some(arg) = args.next_if(|arg| arg.starts_with('-')) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => {
+                eprintln!("dxwmc: unknown option `{}`\n{}", arg, USAGE);
+                exit(2);
+            }
+        }
+    }
+
+    let mut words: Vec<String> = args.collect();
+
+    // `query` also takes it last, e.g. `dxwmc query clients --json`
+    if words.first().is_some_and(|word| word == "query")
+        && words.last().is_some_and(|word| word == "--json")
+    {
+        words.pop();
+        json = true;
+    }
+
+    if words.is_empty() {
+        eprintln!("{}", USAGE);
+        exit(2);
+    }
+
+    if let Err(err) = run(&words, json) {
+        eprintln!("dxwmc: {}", err);
+        exit(1);
+    }
+}
+
+fn run(words: &[String], json: bool) -> Result<()> {
+    let path = socket_path()?;
+
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|_| anyhow!("no running dxwm instance found at {}", path.display()))?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let request = if json {
+        let (command, args) = words.split_first().expect("words is not empty");
+        json!({ "command": command, "args": args }).to_string()
+    } else {
+        words.join(" ")
+    };
+
+    stream.write_all(request.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.shutdown(Shutdown::Write)?;
+
+    if words[0] == "subscribe" {
+        return stream_events(stream, json);
+    }
+
+    let mut reply = String::new();
+    stream
+        .read_to_string(&mut reply)
+        .context("Failed to read reply from dxwm")?;
+    let reply = reply.trim_end();
+
+    if json {
+        let reply: Value = serde_json::from_str(reply).context("Invalid reply from dxwm")?;
+
+        if reply["ok"] != Value::Bool(true) {
+            bail!("{}", reply["error"].as_str().unwrap_or("unknown error"));
+        }
+        if let Some(data) = reply.get("data") {
+            println!("{}", data);
+        }
+    } else if let Some(err) = reply.strip_prefix("error: ") {
+        bail!("{}", err);
+    } else if reply != "ok" {
+        println!("{}", reply);
+    }
+
+    Ok(())
+}
+
+/// Check the subscription reply, then print events until dxwm goes away
+fn stream_events(stream: UnixStream, json: bool) -> Result<()> {
+    stream.set_read_timeout(None)?;
+
+    let mut lines = BufReader::new(stream).lines();
+
+    let reply = lines
+        .next()
+        .ok_or_else(|| anyhow!("dxwm closed the connection"))??;
+
+    if json {
+        let reply: Value = serde_json::from_str(&reply).context("Invalid reply from dxwm")?;
+        if reply["ok"] != Value::Bool(true) {
+            bail!("{}", reply["error"].as_str().unwrap_or("unknown error"));
+        }
+    } else if let Some(err) = reply.strip_prefix("error: ") {
+        bail!("{}", err);
+    }
+
+    let mut stdout = std::io::stdout();
+    for line in lines {
+        writeln!(stdout, "{}", line?)?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Find the socket from `$DXWM_SOCKET` or the `_DXWM_SOCKET` root property
+fn socket_path() -> Result<PathBuf> {
+    if let Some(path) = env::var_os("DXWM_SOCKET").filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+
+    let (conn, screen_num) = RustConnection::connect(None)
+        .map_err(|_| anyhow!("no running dxwm instance found (DXWM_SOCKET unset, no X display)"))?;
+    let root = conn.setup().roots[screen_num].root;
+
+    let atom = conn
+        .intern_atom(true, b"_DXWM_SOCKET")?
+        .reply()
+        .context("Failed to intern _DXWM_SOCKET")?
+        .atom;
+
+    let reply = conn
+        .get_property(false, root, atom, AtomEnum::ANY, 0, 1024)?
+        .reply()
+        .context("Failed to read _DXWM_SOCKET")?;
+
+    if atom == x11rb::NONE || reply.value.is_empty() {
+        bail!("no running dxwm instance found on this display");
+    }
+
+    Ok(PathBuf::from(
+        String::from_utf8_lossy(&reply.value).to_string(),
+    ))
+}
@@ -1,19 +1,46 @@
 use std::collections::HashMap;
 
 use anyhow::Result;
+use serde::Serialize;
+use serde_json::json;
+use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
-use x11rb::CURRENT_TIME;
-use x11rb::{connection::Connection, protocol::xproto::MapRequestEvent};
+use x11rb::wrapper::ConnectionExt as _;
 
+use crate::hints::SizeHints;
+use crate::layout::Rect;
 use crate::wm::WindowManager;
 
-#[derive(Debug, Clone)]
+/// ICCCM `WM_STATE` values
+const WM_STATE_WITHDRAWN: u32 = 0;
+const WM_STATE_NORMAL: u32 = 1;
+const WM_STATE_ICONIC: u32 = 3;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ClientState {
     pub x: i16,
     pub y: i16,
     pub width: u16,
     pub height: u16,
     pub is_fullscreen: bool,
+    pub is_floating: bool,
+    pub is_urgent: bool,
+    /// `_NET_WM_STATE_ABOVE`, kept over the other tiled and floating windows
+    pub is_above: bool,
+    /// `_NET_WM_STATE_BELOW`, kept under the other tiled and floating windows
+    pub is_below: bool,
+    /// Last geometry the client had while floating, restored when it floats again
+    pub floating_geometry: Option<Rect>,
+    /// Parent from WM_TRANSIENT_FOR, dialogs float over it
+    pub transient_for: Option<Window>,
+    #[serde(skip)]
+    pub size_hints: SizeHints,
+    /// WM_HINTS input field, false for clients that never take the focus themselves
+    #[serde(skip)]
+    pub accepts_input: bool,
+    /// Listed WM_TAKE_FOCUS in WM_PROTOCOLS
+    #[serde(skip)]
+    pub takes_focus: bool,
     pub saved_x: i16,
     pub saved_y: i16,
     pub saved_width: u16,
@@ -28,6 +55,15 @@ impl Default for ClientState {
             width: 100,
             height: 100,
             is_fullscreen: false,
+            is_floating: false,
+            is_urgent: false,
+            is_above: false,
+            is_below: false,
+            floating_geometry: None,
+            transient_for: None,
+            size_hints: SizeHints::default(),
+            accepts_input: true,
+            takes_focus: false,
             saved_x: 0,
             saved_y: 0,
             saved_width: 100,
@@ -44,6 +80,15 @@ impl ClientState {
             width,
             height,
             is_fullscreen: false,
+            is_floating: false,
+            is_urgent: false,
+            is_above: false,
+            is_below: false,
+            floating_geometry: None,
+            transient_for: None,
+            size_hints: SizeHints::default(),
+            accepts_input: true,
+            takes_focus: false,
             saved_x: x,
             saved_y: y,
             saved_width: width,
@@ -51,6 +96,11 @@ impl ClientState {
         }
     }
 
+    /// Whether the layouts place this client
+    pub fn is_tiled(&self) -> bool {
+        !self.is_fullscreen && !self.is_floating
+    }
+
     pub fn save_geometry(&mut self) {
         if !self.is_fullscreen {
             self.saved_x = self.x;
@@ -108,30 +158,138 @@ impl WindowManager {
             .focused_client = window;
     }
 
+    /// Find the monitor and workspace id a client is managed on
+    pub fn locate_client(&self, window: Window) -> Option<(usize, u8)> {
+        self.monitors.locate(window)
+    }
+
+    /// Look up a client's state on any monitor or workspace
+    pub fn find_client(&self, window: Window) -> Option<&ClientState> {
+        self.monitors.client(window)
+    }
+
+    /// Focus and raise a client of the current workspace
+    pub fn focus_client(&mut self, window: Window) -> Result<()> {
+        if !self.clients().contains_key(&window) {
+            return Ok(());
+        }
+
+        self.set_focused_client(Some(window));
+
+        self.give_input_focus(window)?;
+
+        self.raise_client(window)?;
+
+        self.update_client_borders()
+    }
+
+    /// Adopt the windows that were already mapped before dxwm started
+    pub fn adopt_existing_windows(&mut self) -> Result<()> {
+        let children = self.conn.query_tree(self.root)?.reply()?.children;
+
+        for window in children {
+            let Ok(attributes) = self.conn.get_window_attributes(window)?.reply() else {
+                continue;
+            };
+
+            if attributes.override_redirect {
+                continue;
+            }
+
+            // windows dxwm hid on other workspaces are unmapped but still iconic
+            if attributes.map_state != MapState::VIEWABLE
+                && self.wm_state(window).ok().flatten() != Some(WM_STATE_ICONIC)
+            {
+                continue;
+            }
+
+            println!("Adopting existing window: {}", window);
+
+            if let Err(err) = self.manage_client(window) {
+                eprintln!("Error adopting window {}: {}", window, err);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The window named by WM_TRANSIENT_FOR, if any
+    fn transient_parent(&self, window: Window) -> Result<Option<Window>> {
+        let reply = self
+            .conn
+            .get_property(
+                false,
+                window,
+                AtomEnum::WM_TRANSIENT_FOR,
+                AtomEnum::WINDOW,
+                0,
+                1,
+            )?
+            .reply()?;
+
+        Ok(reply
+            .value32()
+            .and_then(|mut values| values.next())
+            .filter(|&parent| parent != x11rb::NONE && parent != self.root && parent != window))
+    }
+
+    /// Dialog, utility, splash and toolbar windows float instead of tiling
+    fn is_dialog_type(&self, window: Window) -> Result<bool> {
+        let floating_types = [
+            self.atoms.net_wm_window_type_dialog,
+            self.atoms.net_wm_window_type_utility,
+            self.atoms.net_wm_window_type_splash,
+            self.atoms.net_wm_window_type_toolbar,
+        ];
+
+        Ok(self
+            .window_types(window)?
+            .iter()
+            .any(|t| floating_types.contains(t)))
+    }
+
     /// Manage all the clients
-    pub fn manage_client(&mut self, e: MapRequestEvent) -> Result<()> {
-        let client = e.window;
+    pub fn manage_client(&mut self, client: Window) -> Result<()> {
+        if self.is_dock(client)? {
+            return self.manage_dock(client);
+        }
 
-        println!("Managing new client: {}", client);
+        if self.is_desktop_window(client)? {
+            return self.manage_desktop_window(client);
+        }
 
-        let initial_state = ClientState::default();
+        if let Some((monitor_id, workspace_id)) = self.locate_client(client) {
+            if self.monitors.monitors[monitor_id]
+                .workspaces
+                .current_workspace
+                == workspace_id
+            {
+                self.show_client(client)?;
+                self.conn.flush()?;
+            }
+            return Ok(());
+        }
 
-        self.monitors
-            .current_mut()
-            .workspaces
-            .current_mut()
-            .add_client(client, initial_state);
+        println!("Managing new client: {}", client);
 
-        self.conn.change_window_attributes(
-            client,
-            &ChangeWindowAttributesAux::new()
-                .event_mask(EventMask::ENTER_WINDOW | EventMask::FOCUS_CHANGE)
-                .border_pixel(self.border_unfocused_color),
-        )?;
+        let current = (
+            self.monitors.current_monitor,
+            self.monitors.current().workspaces.current_workspace,
+        );
+
+        // transients live wherever their parent does
+        let transient_for = self.transient_parent(client)?;
+        let (monitor_id, workspace_id) = match transient_for.and_then(|p| self.locate_client(p)) {
+            Some(location) => location,
+            None => self.requested_desktop(client)?.unwrap_or(current),
+        };
 
-        let mut should_fullscreen = false;
+        let size_hints = self.read_size_hints(client)?;
+        let is_floating =
+            size_hints.is_fixed() || transient_for.is_some() || self.is_dialog_type(client)?;
 
-        if let Ok(reply) = self
+        // states the client asked for before mapping its window
+        let wm_states: Vec<Atom> = self
             .conn
             .get_property(
                 false,
@@ -142,75 +300,222 @@ impl WindowManager {
                 1024,
             )?
             .reply()
-        {
-            if let Some(atoms) = reply.value32() {
-                for atom in atoms {
-                    if atom == self.atoms.net_wm_state_fullscreen {
-                        should_fullscreen = true;
-                        break;
-                    }
-                }
-            }
-        }
+            .ok()
+            .and_then(|reply| reply.value32().map(|atoms| atoms.collect()))
+            .unwrap_or_default();
+
+        let is_above = wm_states.contains(&self.atoms.net_wm_state_above);
+        let is_below = !is_above && wm_states.contains(&self.atoms.net_wm_state_below);
+
+        let initial_state = ClientState {
+            is_floating,
+            is_above,
+            is_below,
+            transient_for,
+            size_hints,
+            ..ClientState::default()
+        };
 
-        if should_fullscreen {
-            self.conn.map_window(client)?;
-            self.fullscreen_client(client)?;
-            return Ok(());
-        }
+        self.monitors
+            .add_client(monitor_id, workspace_id, client, initial_state);
+        self.managed.push(client);
+        self.stack.push(client);
+        self.update_wm_hints(client)?;
+        self.update_net_wm_state(client)?;
+
+        self.emit(
+            "client",
+            json!({
+                "change": "managed",
+                "window": client,
+                "monitor": monitor_id,
+                "workspace": workspace_id,
+            }),
+        );
+
+        self.conn.change_window_attributes(
+            client,
+            &ChangeWindowAttributesAux::new()
+                .event_mask(
+                    EventMask::ENTER_WINDOW | EventMask::FOCUS_CHANGE | EventMask::PROPERTY_CHANGE,
+                )
+                .border_pixel(self.border_unfocused_color),
+        )?;
 
         self.conn.configure_window(
             client,
             &ConfigureWindowAux::new().border_width(self.border_width),
         )?;
 
-        self.conn.map_window(client)?;
+        if is_floating {
+            self.place_floating(client, monitor_id)?;
+        }
+
+        // restored onto a workspace that is not the focused one
+        if (monitor_id, workspace_id) != current {
+            if self.monitors.monitors[monitor_id]
+                .workspaces
+                .current_workspace
+                == workspace_id
+            {
+                self.show_client(client)?;
+                self.layout_all_monitors()?;
+            } else {
+                self.hide_client(client)?;
+            }
+
+            self.conn.flush()?;
+            return Ok(());
+        }
+
+        if wm_states.contains(&self.atoms.net_wm_state_fullscreen) {
+            self.show_client(client)?;
+            self.fullscreen_client(client)?;
+            return Ok(());
+        }
+
+        self.show_client(client)?;
 
         self.set_focused_client(Some(client));
-        self.conn
-            .set_input_focus(InputFocus::PARENT, client, CURRENT_TIME)?;
+        self.give_input_focus(client)?;
 
         self.layout()?;
 
+        if is_floating {
+            self.raise_client(client)?;
+        }
+
         self.update_client_borders()?;
 
         self.conn.flush()?;
         Ok(())
     }
 
+    /// Tell unmaps done by dxwm apart from clients withdrawing their window
+    pub fn handle_unmap_notify(&mut self, e: UnmapNotifyEvent) -> Result<()> {
+        if let Some(count) = self.expected_unmaps.get_mut(&e.window) {
+            *count -= 1;
+            if *count == 0 {
+                self.expected_unmaps.remove(&e.window);
+            }
+            return Ok(());
+        }
+
+        if self.locate_client(e.window).is_some() {
+            self.set_wm_state(e.window, WM_STATE_WITHDRAWN)?;
+        }
+
+        self.unmanage_client(e.window)
+    }
+
     /// Unmanage all the clients
     pub fn unmanage_client(&mut self, window: Window) -> Result<()> {
+        if self.unmanage_dock(window)? || self.unmanage_desktop_window(window)? {
+            return Ok(());
+        }
+
+        self.expected_unmaps.remove(&window);
+        self.managed.retain(|&w| w != window);
+        self.stack.retain(|&w| w != window);
+
+        // the drop preview goes with the drag
+        if let Some(drag) = self.drag.take_if(|drag| drag.window == window)
+            && let Some(outline) = drag.outline
+        {
+            self.destroy_outline(outline)?;
+        }
+
+        let Some((monitor_id, workspace_id)) = self.locate_client(window) else {
+            return Ok(());
+        };
+
         println!("Unmanaging client: {}", window);
 
-        self.monitors
-            .current_mut()
-            .workspaces
-            .current_mut()
-            .remove_client(window);
+        let removed = self.monitors.remove_client(window);
+        if removed.is_some() {
+            self.emit("client", json!({ "change": "unmanaged", "window": window }));
+        }
 
-        if self.focused_client() == Some(window) {
-            if !self.clients().is_empty() {
-                let next_window = *self.clients().keys().next().unwrap();
-                self.set_focused_client(Some(next_window));
+        let is_current = monitor_id == self.monitors.current_monitor
+            && workspace_id == self.monitors.current().workspaces.current_workspace;
 
-                self.conn
-                    .set_input_focus(InputFocus::PARENT, next_window, CURRENT_TIME)?;
-            } else {
-                self.set_focused_client(None);
+        if is_current {
+            // a closed dialog hands the focus back to its parent
+            let parent = removed.and_then(|state| state.transient_for);
+            if let Some(parent) = parent
+                && self.clients().contains_key(&parent)
+            {
+                self.set_focused_client(Some(parent));
             }
-        }
 
-        self.layout()?;
+            if let Some(focused) = self.focused_client() {
+                self.give_input_focus(focused)?;
+            }
+
+            self.layout()?;
 
-        if !self.clients().is_empty() {
-            self.update_client_borders()?;
+            if !self.clients().is_empty() {
+                self.update_client_borders()?;
+            }
+        } else if self.monitors.monitors[monitor_id]
+            .workspaces
+            .current_workspace
+            == workspace_id
+        {
+            self.layout_all_monitors()?;
         }
 
-        self.restack_alerts()?;
+        self.restack()?;
         self.conn.flush()?;
         Ok(())
     }
 
+    /// Unmap a client dxwm is hiding, expecting the UnmapNotify it causes
+    pub fn hide_client(&mut self, window: Window) -> Result<()> {
+        let attributes = self.conn.get_window_attributes(window)?.reply()?;
+
+        if attributes.map_state != MapState::UNMAPPED {
+            *self.expected_unmaps.entry(window).or_insert(0) += 1;
+            self.conn.unmap_window(window)?;
+        }
+
+        self.set_wm_state(window, WM_STATE_ICONIC)
+    }
+
+    /// Map a client and mark it as in the normal state
+    pub fn show_client(&mut self, window: Window) -> Result<()> {
+        self.conn.map_window(window)?;
+        self.set_wm_state(window, WM_STATE_NORMAL)
+    }
+
+    fn set_wm_state(&self, window: Window, state: u32) -> Result<()> {
+        self.conn.change_property32(
+            PropMode::REPLACE,
+            window,
+            self.atoms.wm_state,
+            self.atoms.wm_state,
+            &[state, x11rb::NONE],
+        )?;
+        Ok(())
+    }
+
+    /// The state in the ICCCM WM_STATE property of a window, if it has one
+    fn wm_state(&self, window: Window) -> Result<Option<u32>> {
+        let reply = self
+            .conn
+            .get_property(
+                false,
+                window,
+                self.atoms.wm_state,
+                self.atoms.wm_state,
+                0,
+                2,
+            )?
+            .reply()?;
+
+        Ok(reply.value32().and_then(|mut values| values.next()))
+    }
+
     /// focus next client in the workspace
     pub fn focus_next(&mut self) -> Result<()> {
         if self.clients().is_empty() {
@@ -234,17 +539,12 @@ impl WindowManager {
 
         self.set_focused_client(Some(next_client));
 
-        self.conn
-            .set_input_focus(InputFocus::PARENT, next_client, CURRENT_TIME)?;
+        self.give_input_focus(next_client)?;
 
-        self.conn.configure_window(
-            next_client,
-            &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-        )?;
+        self.raise_client(next_client)?;
 
         self.update_client_borders()?;
 
-        self.restack_alerts()?;
         self.conn.flush()?;
         Ok(())
     }
@@ -277,17 +577,12 @@ impl WindowManager {
 
         self.set_focused_client(Some(prev_window));
 
-        self.conn
-            .set_input_focus(InputFocus::PARENT, prev_window, CURRENT_TIME)?;
+        self.give_input_focus(prev_window)?;
 
-        self.conn.configure_window(
-            prev_window,
-            &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-        )?;
+        self.raise_client(prev_window)?;
 
         self.update_client_borders()?;
 
-        self.restack_alerts()?;
         self.conn.flush()?;
         Ok(())
     }
@@ -302,7 +597,7 @@ impl WindowManager {
                 .into_iter()
                 .filter(|w| {
                     if let Some(state) = workspace.clients.get(w) {
-                        !state.is_fullscreen
+                        state.is_tiled()
                     } else {
                         false
                     }
@@ -338,7 +633,7 @@ impl WindowManager {
                 .into_iter()
                 .filter(|w| {
                     if let Some(state) = workspace.clients.get(w) {
-                        !state.is_fullscreen
+                        state.is_tiled()
                     } else {
                         false
                     }
@@ -369,12 +664,124 @@ impl WindowManager {
         Ok(())
     }
 
+    /// Focus follows the mouse, onto whichever monitor the client lives on
+    pub fn handle_enter_notify(&mut self, e: EnterNotifyEvent) -> Result<()> {
+        let Some((monitor_id, workspace_id)) = self.locate_client(e.event) else {
+            return Ok(());
+        };
+
+        if self.focused_client() == Some(e.event) {
+            return Ok(());
+        }
+
+        if monitor_id != self.monitors.current_monitor {
+            self.monitors.switch_to(monitor_id);
+            self.emit(
+                "workspace",
+                json!({ "monitor": monitor_id, "workspace": workspace_id }),
+            );
+        }
+
+        self.set_focused_client(Some(e.event));
+
+        self.give_input_focus(e.event)?;
+
+        self.update_client_borders()?;
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Answer a client asking for a new geometry
+    pub fn handle_configure_request(&mut self, e: ConfigureRequestEvent) -> Result<()> {
+        let Some(mut state) = self.find_client(e.window).cloned() else {
+            // not ours to lay out, give it what it asked for
+            self.conn
+                .configure_window(e.window, &ConfigureWindowAux::from_configure_request(&e))?;
+            self.conn.flush()?;
+            return Ok(());
+        };
+
+        if state.is_floating && !state.is_fullscreen {
+            let mask = e.value_mask;
+            let x = if mask.contains(ConfigWindow::X) {
+                e.x
+            } else {
+                state.x
+            };
+            let y = if mask.contains(ConfigWindow::Y) {
+                e.y
+            } else {
+                state.y
+            };
+            let width = if mask.contains(ConfigWindow::WIDTH) {
+                e.width
+            } else {
+                state.width
+            };
+            let height = if mask.contains(ConfigWindow::HEIGHT) {
+                e.height
+            } else {
+                state.height
+            };
+
+            self.move_resize_client(e.window, x, y, width as u32, height as u32)?;
+
+            if let Some(moved) = self.find_client(e.window) {
+                state = moved.clone();
+            }
+        }
+
+        // tiled and fullscreen clients keep their geometry, but are told where they are
+        let border_width = if state.is_fullscreen {
+            0
+        } else {
+            self.border_width as u16
+        };
+
+        let notify = ConfigureNotifyEvent {
+            response_type: CONFIGURE_NOTIFY_EVENT,
+            sequence: 0,
+            event: e.window,
+            window: e.window,
+            above_sibling: x11rb::NONE,
+            x: state.x,
+            y: state.y,
+            width: state.width,
+            height: state.height,
+            border_width,
+            override_redirect: false,
+        };
+
+        self.conn
+            .send_event(false, e.window, EventMask::STRUCTURE_NOTIFY, notify)?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// React to property changes on docks and clients
+    pub fn handle_property_notify(&mut self, e: PropertyNotifyEvent) -> Result<()> {
+        if self.docks.contains_key(&e.window)
+            && (e.atom == self.atoms.net_wm_strut || e.atom == self.atoms.net_wm_strut_partial)
+        {
+            self.update_dock_strut(e.window)?;
+        } else if e.atom == u32::from(AtomEnum::WM_NORMAL_HINTS) {
+            self.update_size_hints(e.window)?;
+        } else if e.atom == u32::from(AtomEnum::WM_HINTS) || e.atom == self.atoms.wm_protocols {
+            self.update_wm_hints(e.window)?;
+        }
+
+        Ok(())
+    }
+
     /// update client borders
     pub fn update_client_borders(&mut self) -> Result<()> {
-        for &client in self.clients().keys() {
+        for (&client, state) in self.clients() {
             let is_focused = self.focused_client() == Some(client);
             let color = if is_focused {
                 self.border_focused_color
+            } else if state.is_urgent {
+                self.config.appearance.border_urgent
             } else {
                 self.border_unfocused_color
             };
@@ -385,7 +792,6 @@ impl WindowManager {
             )?;
         }
 
-        self.restack_alerts()?;
         self.conn.flush()?;
         Ok(())
     }
@@ -393,7 +799,7 @@ impl WindowManager {
     /// close client, send a WM_DELE_WINDOW and if its not valid force destroy
     pub fn close_client(&mut self, window: Window) -> Result<()> {
         if self.client_supports_protocol(window, self.atoms.wm_delete_window)? {
-            self.send_delete_window(window)?;
+            self.send_protocol(window, self.atoms.wm_delete_window)?;
             println!("Sent WM_DELETE_WINDOW to window {}", window);
         } else {
             println!(
@@ -409,7 +815,7 @@ impl WindowManager {
     }
 
     /// check if the client support an especific protocol
-    fn client_supports_protocol(&self, window: Window, protocol: Atom) -> Result<bool> {
+    pub fn client_supports_protocol(&self, window: Window, protocol: Atom) -> Result<bool> {
         let protocols = match self
             .conn
             .get_property(
@@ -426,7 +832,7 @@ impl WindowManager {
             Err(_) => return Ok(false),
         };
 
-        if protocols.type_ != AtomEnum::ATOM.into() || protocols.format != 32 {
+        if protocols.type_ != u32::from(AtomEnum::ATOM) || protocols.format != 32 {
             return Ok(false);
         }
 
@@ -438,21 +844,15 @@ impl WindowManager {
         Ok(atoms.contains(&protocol))
     }
 
-    /// Send a "delete_window" event to x11 server
-    fn send_delete_window(&self, window: Window) -> Result<()> {
+    /// Send a WM_PROTOCOLS message such as WM_DELETE_WINDOW or WM_TAKE_FOCUS
+    pub fn send_protocol(&self, window: Window, protocol: Atom) -> Result<()> {
         let event = ClientMessageEvent {
             response_type: CLIENT_MESSAGE_EVENT,
             format: 32,
             sequence: 0,
             window,
             type_: self.atoms.wm_protocols,
-            data: ClientMessageData::from([
-                self.atoms.wm_delete_window,
-                x11rb::CURRENT_TIME,
-                0,
-                0,
-                0,
-            ]),
+            data: ClientMessageData::from([protocol, x11rb::CURRENT_TIME, 0, 0, 0]),
         };
 
         self.conn
@@ -471,7 +871,7 @@ impl WindowManager {
 
     /// Toggle the **fullscreen state** on the focused window
     pub fn toggle_fullscreen(&mut self, window: Window) -> Result<()> {
-        if let Some(state) = self.clients_mut().get_mut(&window) {
+        if let Some(state) = self.monitors.client(window) {
             if state.is_fullscreen {
                 self.unfullscreen_client(window)?;
             } else {
@@ -487,7 +887,10 @@ impl WindowManager {
         println!("Setting client {} to fullscreen", window);
 
         let geometry = {
-            let monitor = self.monitors.current();
+            let monitor = match self.locate_client(window) {
+                Some((monitor_id, _)) => &self.monitors.monitors[monitor_id],
+                None => self.monitors.current(),
+            };
             (monitor.x, monitor.y, monitor.width, monitor.height)
         };
 
@@ -500,23 +903,10 @@ impl WindowManager {
                 .y(y as i32)
                 .width(width as u32)
                 .height(height as u32)
-                .border_width(0)
-                .stack_mode(StackMode::ABOVE),
+                .border_width(0),
         )?;
 
-        self.conn.change_property(
-            PropMode::REPLACE,
-            window,
-            self.atoms.net_wm_state,
-            AtomEnum::ATOM,
-            8,
-            1,
-            &[self.atoms.net_wm_state_fullscreen as u8],
-        )?;
-
-        self.conn.flush()?;
-
-        if let Some(state) = self.clients_mut().get_mut(&window) {
+        if let Some(state) = self.monitors.client_mut(window) {
             state.save_geometry();
             state.is_fullscreen = true;
 
@@ -526,6 +916,10 @@ impl WindowManager {
             state.height = height;
         }
 
+        self.update_net_wm_state(window)?;
+        self.restack()?;
+        self.conn.flush()?;
+
         Ok(())
     }
 
@@ -533,7 +927,7 @@ impl WindowManager {
     pub fn unfullscreen_client(&mut self, window: Window) -> Result<()> {
         println!("Removing fullscren from client {}", window);
 
-        if let Some(state) = self.clients_mut().get_mut(&window) {
+        if let Some(state) = self.monitors.client_mut(window) {
             state.is_fullscreen = false;
 
             let saved_x = state.saved_x;
@@ -553,7 +947,7 @@ impl WindowManager {
                     .border_width(self.border_width),
             )?;
 
-            self.conn.delete_property(window, self.atoms.net_wm_state)?;
+            self.update_net_wm_state(window)?;
 
             self.conn.flush()?;
             self.layout()?;
@@ -571,7 +965,6 @@ impl WindowManager {
         state1: Atom,
         state2: Atom,
     ) -> Result<()> {
-        // TODO: implement float atoms
         if state1 == self.atoms.net_wm_state_fullscreen
             || state2 == self.atoms.net_wm_state_fullscreen
         {
@@ -583,6 +976,15 @@ impl WindowManager {
             }
         }
 
+        for (state, above) in [
+            (self.atoms.net_wm_state_above, true),
+            (self.atoms.net_wm_state_below, false),
+        ] {
+            if state1 == state || state2 == state {
+                self.change_stack_state(window, above, action)?;
+            }
+        }
+
         Ok(())
     }
 }
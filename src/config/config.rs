@@ -1,12 +1,31 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
 use std::process::Command;
 
+use serde::{Deserialize, Deserializer};
+use toml::Spanned;
+use x11rb::protocol::xproto::ModMask;
+
+use crate::floating::FloatingConfig;
+use crate::keybindings::KeyAction;
+use crate::keysyms::parse_key_chord;
+use crate::layout::LayoutConfig;
+use crate::layouts::layout_by_name;
+
 pub const BORDER_WIDTH: u32 = 1;
 pub const MARGIN: u32 = 5;
 
+/// Largest gap or screen padding the config accepts, keeps layout arithmetic in range
+const MAX_SPACING: i16 = 1000;
+
 pub const BACKGROUND: u32 = 0x1D2021;
 pub const FOREGROUND: u32 = 0xFBFBFB;
 pub const BORDER_FOCUSED: u32 = 0xCCCCCC;
 pub const BORDER_UNFOCUSED: u32 = 0x5C5C5C;
+pub const BORDER_URGENT: u32 = 0xCC241D;
 pub const SELECTED: u32 = 0x5C5C5C;
 pub const FONT_NAME: &str = "Terminess Nerd Font";
 
@@ -15,16 +34,336 @@ pub const FILEMANAGER_APP: &str = "pcmanfm";
 pub const EDITOR_APP: &str = "emacs";
 pub const BROWSER_APP: &str = "qutebrowser";
 
-pub fn launch_dmenu() {
+pub const NUM_WORKSPACES: u8 = 9;
+
+/// Runtime configuration, read from `$XDG_CONFIG_HOME/dxwm/config`.
+///
+/// Every field falls back to the compile-time defaults above when it is
+/// missing from the file (or when there is no file at all).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub workspaces: u8,
+    pub appearance: Appearance,
+    pub apps: Apps,
+    pub layout: LayoutConfig,
+    pub floating: FloatingConfig,
+    #[serde(rename = "submap")]
+    pub submaps: Vec<SubmapConfig>,
+    #[serde(rename = "bind")]
+    raw_bindings: Vec<RawBinding>,
+    #[serde(skip)]
+    pub bindings: Vec<BindingConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Appearance {
+    pub border_width: u32,
+    pub margin: u32,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub background: u32,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub foreground: u32,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub border_focused: u32,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub border_unfocused: u32,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub border_urgent: u32,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub selected: u32,
+    pub font: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Apps {
+    pub terminal: String,
+    pub file_manager: String,
+    pub editor: String,
+    pub browser: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubmapConfig {
+    pub name: String,
+    #[serde(default)]
+    pub oneshot: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBinding {
+    mode: Option<String>,
+    key: Spanned<String>,
+    action: Spanned<String>,
+}
+
+/// Where the range-checked values sit in the file, read in a second pass so
+/// their errors can point at a line
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ValueSpans {
+    workspaces: Option<Spanned<u8>>,
+    layout: LayoutSpans,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct LayoutSpans {
+    default: Option<Spanned<String>>,
+    layouts: Option<Spanned<Vec<Spanned<String>>>>,
+    master_ratio: Option<Spanned<f32>>,
+    nmaster: Option<Spanned<usize>>,
+    gap_size: Option<Spanned<i16>>,
+    screen_padding: Option<Spanned<i16>>,
+}
+
+/// A validated `[[bind]]` entry, the keycode is resolved once grabbing.
+#[derive(Debug, Clone)]
+pub struct BindingConfig {
+    pub mode: Option<String>,
+    pub keysym: u32,
+    pub modifiers: ModMask,
+    pub action: KeyAction,
+}
+
+/// Error produced while reading the config file
+#[derive(Debug)]
+pub struct ConfigError {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            workspaces: NUM_WORKSPACES,
+            appearance: Appearance::default(),
+            apps: Apps::default(),
+            layout: LayoutConfig::default(),
+            floating: FloatingConfig::default(),
+            submaps: Vec::new(),
+            raw_bindings: Vec::new(),
+            bindings: Vec::new(),
+        }
+    }
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            border_width: BORDER_WIDTH,
+            margin: MARGIN,
+            background: BACKGROUND,
+            foreground: FOREGROUND,
+            border_focused: BORDER_FOCUSED,
+            border_unfocused: BORDER_UNFOCUSED,
+            border_urgent: BORDER_URGENT,
+            selected: SELECTED,
+            font: FONT_NAME.to_string(),
+        }
+    }
+}
+
+impl Default for Apps {
+    fn default() -> Self {
+        Self {
+            terminal: TERMINAL_APP.to_string(),
+            file_manager: FILEMANAGER_APP.to_string(),
+            editor: EDITOR_APP.to_string(),
+            browser: BROWSER_APP.to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Return the config file location
+    pub fn path() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => env::home_dir()?.join(".config"),
+        };
+
+        Some(base.join("dxwm").join("config"))
+    }
+
+    /// Read the config file, a missing file yields the default config
+    pub fn load() -> Result<Self, ConfigError> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(source) => Self::parse(&source),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(ConfigError {
+                line: None,
+                message: format!("{}: {}", path.display(), err),
+            }),
+        }
+    }
+
+    /// Parse and validate the config from its source text
+    pub fn parse(source: &str) -> Result<Self, ConfigError> {
+        let mut config: Config = toml::from_str(source).map_err(|err| ConfigError {
+            line: err.span().map(|span| line_of(source, span.start)),
+            message: err.message().to_string(),
+        })?;
+
+        let spans: ValueSpans = toml::from_str(source).unwrap_or_default();
+        let invalid = |line: Option<usize>, message: &str| ConfigError {
+            line,
+            message: message.to_string(),
+        };
+
+        if config.workspaces == 0 {
+            return Err(invalid(
+                line_at(source, &spans.workspaces),
+                "workspaces must be at least 1",
+            ));
+        }
+
+        let layout = &config.layout;
+        if !(layout.master_ratio > 0.0 && layout.master_ratio < 1.0) {
+            return Err(invalid(
+                line_at(source, &spans.layout.master_ratio),
+                "master_ratio must be between 0 and 1",
+            ));
+        }
+        if layout.nmaster == 0 {
+            return Err(invalid(
+                line_at(source, &spans.layout.nmaster),
+                "nmaster must be at least 1",
+            ));
+        }
+        if !(0..=MAX_SPACING).contains(&layout.gap_size) {
+            return Err(invalid(
+                line_at(source, &spans.layout.gap_size),
+                &format!("gap_size must be between 0 and {}", MAX_SPACING),
+            ));
+        }
+        if !(0..=MAX_SPACING).contains(&layout.screen_padding) {
+            return Err(invalid(
+                line_at(source, &spans.layout.screen_padding),
+                &format!("screen_padding must be between 0 and {}", MAX_SPACING),
+            ));
+        }
+
+        if layout.layouts.is_empty() {
+            return Err(invalid(
+                line_at(source, &spans.layout.layouts),
+                "layouts must name at least one layout",
+            ));
+        }
+        if layout_by_name(&layout.current).is_none() {
+            return Err(invalid(
+                line_at(source, &spans.layout.default),
+                &format!("unknown layout `{}`", layout.current),
+            ));
+        }
+        if let Some(name) = layout
+            .layouts
+            .iter()
+            .find(|name| layout_by_name(name).is_none())
+        {
+            let line = spans
+                .layout
+                .layouts
+                .as_ref()
+                .and_then(|list| list.get_ref().iter().find(|entry| entry.get_ref() == name))
+                .map(|entry| line_of(source, entry.span().start));
+
+            return Err(invalid(line, &format!("unknown layout `{}`", name)));
+        }
+
+        for raw in std::mem::take(&mut config.raw_bindings) {
+            let (keysym, modifiers) =
+                parse_key_chord(raw.key.get_ref()).map_err(|message| ConfigError {
+                    line: Some(line_of(source, raw.key.span().start)),
+                    message,
+                })?;
+
+            let action = raw
+                .action
+                .get_ref()
+                .parse::<KeyAction>()
+                .map_err(|message| ConfigError {
+                    line: Some(line_of(source, raw.action.span().start)),
+                    message,
+                })?;
+
+            config.bindings.push(BindingConfig {
+                mode: raw.mode,
+                keysym,
+                modifiers,
+                action,
+            });
+        }
+
+        Ok(config)
+    }
+}
+
+/// Line of a value read by `ValueSpans`, if the file sets it
+fn line_at<T>(source: &str, value: &Option<Spanned<T>>) -> Option<usize> {
+    value
+        .as_ref()
+        .map(|value| line_of(source, value.span().start))
+}
+
+/// Convert a byte offset in `source` into a 1-based line number
+fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+/// Accept colors as `"#rrggbb"` strings or plain integers
+fn deserialize_color<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Color {
+        Hex(String),
+        Int(u32),
+    }
+
+    match Color::deserialize(deserializer)? {
+        Color::Int(value) => Ok(value),
+        Color::Hex(hex) => {
+            let digits = hex.strip_prefix('#').unwrap_or(&hex);
+            u32::from_str_radix(digits, 16)
+                .ok()
+                .filter(|_| digits.len() == 6)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid color `{}`", hex)))
+        }
+    }
+}
+
+pub fn launch_dmenu(appearance: &Appearance) {
     Command::new("dmenu_run")
         .arg("-fn")
-        .arg(format!("{}:size=9", FONT_NAME))
+        .arg(format!("{}:size=9", appearance.font))
         .arg("-nb")
-        .arg(format!("#{:06x}", BACKGROUND))
+        .arg(format!("#{:06x}", appearance.background))
         .arg("-nf")
-        .arg(format!("#{:06x}", FOREGROUND))
+        .arg(format!("#{:06x}", appearance.foreground))
         .arg("-sb")
-        .arg(format!("#{:06x}", SELECTED))
+        .arg(format!("#{:06x}", appearance.selected))
         .spawn()
         .ok();
 }
@@ -54,3 +393,73 @@ pub fn launch_dmenu() {
   (fg-alt     '("#d5c4a1" "#cccccc" "brightwhite")) ; fg2
 
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Line the error for `source` is reported on
+    fn error_line(source: &str) -> Option<usize> {
+        Config::parse(source).err().and_then(|err| err.line)
+    }
+
+    #[test]
+    fn empty_config_is_the_default() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.workspaces, NUM_WORKSPACES);
+        assert!(config.bindings.is_empty());
+    }
+
+    #[test]
+    fn syntax_errors_report_their_line() {
+        assert_eq!(error_line("workspaces = 4\n\n[layout\n"), Some(3));
+    }
+
+    #[test]
+    fn unknown_fields_report_their_line() {
+        assert_eq!(
+            error_line("[layout]\nmaster_ratio = 0.5\ngaps = 4\n"),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn bad_colors_report_their_line() {
+        let source = "[appearance]\nborder_width = 2\nborder_focused = \"#12345\"\n";
+        let err = Config::parse(source).unwrap_err();
+
+        assert_eq!(err.line, Some(3));
+        assert!(err.message.contains("invalid color `#12345`"));
+    }
+
+    #[test]
+    fn out_of_range_layout_values_report_their_line() {
+        assert_eq!(error_line("[layout]\nmaster_ratio = 1.5\n"), Some(2));
+        assert_eq!(
+            error_line("[layout]\nnmaster = 1\ngap_size = 5000\n"),
+            Some(3)
+        );
+        assert_eq!(error_line("workspaces = 0\n"), Some(1));
+    }
+
+    #[test]
+    fn bad_bindings_report_their_line() {
+        let bad_key = "[[bind]]\nkey = \"Super+Nope\"\naction = \"close\"\n";
+        assert_eq!(error_line(bad_key), Some(2));
+
+        let bad_action = "[[bind]]\nkey = \"Super+q\"\naction = \"explode\"\n";
+        assert_eq!(error_line(bad_action), Some(3));
+    }
+
+    #[test]
+    fn parses_bindings() {
+        let source =
+            "[[bind]]\nmode = \"music\"\nkey = \"Super+p\"\naction = \"spawn mpc toggle\"\n";
+        let config = Config::parse(source).unwrap();
+
+        assert_eq!(config.bindings.len(), 1);
+        assert_eq!(config.bindings[0].mode.as_deref(), Some("music"));
+        assert_eq!(config.bindings[0].keysym, 0x70);
+        assert_eq!(config.bindings[0].modifiers, ModMask::M4);
+    }
+}
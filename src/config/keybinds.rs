@@ -1,15 +1,17 @@
 use std::process::{exit, Command};
 
-use crate::keybindings::KeyAction;
+use crate::keybindings::{Direction, KeyAction};
 use crate::keyboard::{normalize_modifiers, KeyboardGrabber};
+use crate::keysyms::{format_key_chord, parse_key_chord};
 use crate::utils::*;
 use crate::wm::WindowManager;
 use anyhow::Result;
+use serde_json::json;
 use x11rb::connection::Connection;
 use x11rb::protocol::xproto::KeyPressEvent;
 use x11rb::protocol::xproto::ModMask;
 
-use super::config::{launch_dmenu, FILEMANAGER_APP, TERMINAL_APP};
+use super::config::{launch_dmenu, BindingConfig, Config};
 
 impl WindowManager {
     pub fn setup_keybindings(&mut self) -> Result<()> {
@@ -17,551 +19,111 @@ impl WindowManager {
         let screen = &setup.roots[self.screen_num];
         let grabber = KeyboardGrabber::new(&self.conn, screen, setup)?;
 
-        // === MODO NORMAL ===
-        for i in 1..=9 {
-            let keysym = match i {
-                1 => XK_1,
-                2 => XK_2,
-                3 => XK_3,
-                4 => XK_4,
-                5 => XK_5,
-                6 => XK_6,
-                7 => XK_7,
-                8 => XK_8,
-                9 => XK_9,
-                _ => continue,
-            };
-
-            if let Some(key) = grabber.keysym_to_keycode(keysym) {
-                let workspace_id = i;
-                self.keybindings.bind_normal(
-                    key,
-                    ModMask::M4,
-                    KeyAction::SwitchWorkspace(workspace_id),
-                );
-            }
-        }
-
-        // Super+Shift+1-9: Mover ventana a workspace
-        for i in 1..=9 {
-            let keysym = match i {
-                1 => XK_1,
-                2 => XK_2,
-                3 => XK_3,
-                4 => XK_4,
-                5 => XK_5,
-                6 => XK_6,
-                7 => XK_7,
-                8 => XK_8,
-                9 => XK_9,
-                _ => continue,
-            };
-
-            if let Some(key) = grabber.keysym_to_keycode(keysym) {
-                let workspace_id = i;
-                self.keybindings.bind_normal(
-                    key,
-                    ModMask::M4 | ModMask::SHIFT,
-                    KeyAction::MoveToWorkspace(workspace_id),
-                );
-            }
-        }
-
-        // Super+, (comma): Cambiar al monitor anterior
-        if let Some(key) = grabber.keysym_to_keycode(XK_COMMA) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.focus_prev_monitor().ok();
-                }),
-            );
-        }
-
-        // Super+. (period): Cambiar al monitor siguiente
-        if let Some(key) = grabber.keysym_to_keycode(XK_PERIOD) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.focus_next_monitor().ok();
-                }),
-            );
-        }
-
-        // Super+Shift+, (comma): Mover ventana al monitor anterior
-        if let Some(key) = grabber.keysym_to_keycode(XK_COMMA) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4 | ModMask::SHIFT,
-                KeyAction::Custom(|wm| {
-                    let prev_monitor = wm.monitors.prev_monitor_id();
-                    wm.move_focused_to_monitor(prev_monitor).ok();
-                }),
-            );
-        }
-
-        // Super+Shift+. (period): Mover ventana al monitor siguiente
-        if let Some(key) = grabber.keysym_to_keycode(XK_PERIOD) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4 | ModMask::SHIFT,
-                KeyAction::Custom(|wm| {
-                    let next_monitor = wm.monitors.next_monitor_id();
-                    wm.move_focused_to_monitor(next_monitor).ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_ESCAPE) {
-            self.keybindings
-                .bind_normal(key, ModMask::M4 | ModMask::SHIFT, KeyAction::Quit);
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_RETURN) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Spawn(TERMINAL_APP.to_string()),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_C) {
-            self.keybindings
-                .bind_normal(key, ModMask::M4 | ModMask::SHIFT, KeyAction::CloseWindow);
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_N) {
-            self.keybindings
-                .bind_normal(key, ModMask::M4, KeyAction::EnterMode("nav".to_string()));
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_A) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::EnterMode("apps".to_string()),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_S) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::EnterMode("alerts".to_string()),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_SPACE) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.next_layout().ok();
-                    wm.draw_alert(format!(
-                        "[LAY] {:?}",
-                        wm.monitors
-                            .current()
-                            .workspaces
-                            .current()
-                            .layout_config
-                            .current
-                    ))
-                    .ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_J) {
-            self.keybindings
-                .bind_normal(key, ModMask::M4, KeyAction::FocusNext);
-        }
+        self.keybindings.add_submap("nav".to_string(), false);
+        self.keybindings.add_submap("apps".to_string(), true);
+        self.keybindings.add_submap("alerts".to_string(), true);
+        self.keybindings.add_submap("resize".to_string(), false);
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_K) {
+        for submap in &self.config.submaps {
             self.keybindings
-                .bind_normal(key, ModMask::M4, KeyAction::FocusPrev);
+                .add_submap(submap.name.clone(), submap.oneshot);
         }
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_J) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4 | ModMask::SHIFT,
-                KeyAction::Custom(|wm| {
-                    wm.swap_next().ok();
-                }),
-            );
-        }
+        let mut bindings = Vec::new();
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_K) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4 | ModMask::SHIFT,
-                KeyAction::Custom(|wm| {
-                    wm.swap_prev().ok();
+        for (mode, chord, action) in default_bindings(&self.config) {
+            match parse_key_chord(&chord) {
+                Ok((keysym, modifiers)) => bindings.push(BindingConfig {
+                    mode: mode.map(str::to_string),
+                    keysym,
+                    modifiers,
+                    action,
                 }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_H) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.decrease_master_ratio().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_L) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.increase_master_ratio().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_I) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.increase_nmaster().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_D) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.decrease_nmaster().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_B) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.banish().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_F) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    if let Some(window) = wm.focused_client() {
-                        wm.toggle_fullscreen(window).ok();
-                    }
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_G) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.clear_alerts().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_R) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4 | ModMask::SHIFT,
-                KeyAction::Custom(|wm| {
-                    println!("🔄 Manually refreshing monitors...");
-                    if let Ok(changes) = wm.monitors.refresh(&wm.conn, wm.root) {
-                        wm.handle_monitor_changes(changes).ok();
-                        wm.draw_alert("Monitors refreshed".to_string()).ok();
-                    }
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_TAB) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.cycle_last_workspace().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_RETURN) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4 | ModMask::SHIFT,
-                KeyAction::Custom(|wm| {
-                    wm.promote_to_master().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_EQUAL) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4 | ModMask::SHIFT,
-                KeyAction::Custom(|wm| {
-                    wm.increase_gap().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_MINUS) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::M4,
-                KeyAction::Custom(|wm| {
-                    wm.decrease_gap().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_AUDIO_RAISE_VOL) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    get_command_output("pactl set-sink-volume @DEFAULT_SINK@ +10%");
-                    let percentage = get_volume();
-                    let status = if is_muted() { "MUTED" } else { "VOL" };
-
-                    wm.draw_alert(format!("[{}] {}%", status, percentage)).ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_AUDIO_LOWER_VOL) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    get_command_output("pactl set-sink-volume @DEFAULT_SINK@ -10%");
-                    let percentage = get_volume();
-                    let status = if is_muted() { "MUTED" } else { "VOL" };
-
-                    wm.draw_alert(format!("[{}] {}%", status, percentage)).ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_AUDIO_MUTE) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    let _ = get_command_output("pamixer --toggle-mute");
-                    let percentage = get_volume();
-                    let status = if is_muted() { "MUTED" } else { "VOL" };
-
-                    wm.draw_alert(format!("[{}] {}%", status, percentage)).ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_AUDIO_MIC_MUTE) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    let _ = get_command_output("pamixer --default-source --toggle-mute");
-
-                    let muted =
-                        get_command_output("pamixer --default-source --get-mute").trim() == "true";
-
-                    let msg = if muted { "[MIC] MUTED" } else { "[MIC] LIVE" };
-                    wm.draw_alert(msg.to_string()).ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_PRINT) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    Command::new("sh")
-                        .arg("-c")
-                        .arg("maim | xclip -selection clipboard -t image/png -i")
-                        .spawn()
-                        .unwrap()
-                        .wait()
-                        .ok();
-
-                    wm.draw_alert("[SCR] FULL".to_string()).ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_PRINT) {
-            self.keybindings.bind_normal(
-                key,
-                ModMask::SHIFT,
-                KeyAction::Custom(|wm| {
-                    Command::new("sh")
-                        .arg("-c")
-                        .arg("maim -s | xclip -selection clipboard -t image/png -i")
-                        .spawn()
-                        .unwrap()
-                        .wait()
-                        .ok();
-
-                    wm.draw_alert("[SCR] AREA".to_string()).ok();
-                }),
-            );
+                Err(err) => eprintln!("Invalid default binding `{}`: {}", chord, err),
+            }
         }
 
-        // === SUBMAP: NAV ===
-        self.keybindings.add_submap("nav".to_string(), false);
+        // bindings from the config file override the defaults on the same keys
+        bindings.extend(self.config.bindings.iter().cloned());
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_J) {
-            self.keybindings.bind_in_mode(
-                "nav",
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    wm.focus_next().ok();
-                }),
-            );
-        }
+        for binding in bindings {
+            let chord = format_key_chord(binding.keysym, binding.modifiers);
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_K) {
-            self.keybindings.bind_in_mode(
-                "nav",
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    wm.focus_prev().ok();
-                }),
-            );
-        }
-
-        if let Some(key) = grabber.keysym_to_keycode(XK_ESCAPE) {
-            self.keybindings
-                .bind_in_mode("nav", key, ModMask::default(), KeyAction::ExitMode);
-        }
-
-        // === SUBMAP: APPS (oneshot) ===
-        self.keybindings.add_submap("apps".to_string(), true);
+            let Some(key) = grabber.keysym_to_keycode(binding.keysym) else {
+                eprintln!("No keycode for `{}`, skipping binding", chord);
+                continue;
+            };
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_T) {
-            self.keybindings.bind_in_mode(
-                "apps",
-                key,
-                ModMask::default(),
-                KeyAction::Spawn(TERMINAL_APP.to_string()),
-            );
+            match &binding.mode {
+                Some(mode) if self.keybindings.submaps.contains_key(mode) => {
+                    self.keybindings
+                        .bind_in_mode(mode, key, binding.modifiers, binding.action);
+                }
+                Some(mode) => eprintln!("Unknown submap `{}` for `{}`, skipping", mode, chord),
+                None => {
+                    self.keybindings
+                        .bind_normal(key, binding.modifiers, binding.action);
+                }
+            }
         }
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_E) {
-            self.keybindings.bind_in_mode(
-                "apps",
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    Command::new("emacsclient").arg("-c").spawn().ok();
-                    wm.draw_alert("[EMACS] SPAWN".to_string()).ok(); // TODO: remove???
-                }),
-            );
-        }
+        self.update_grabs()?;
+        Ok(())
+    }
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_F) {
-            self.keybindings.bind_in_mode(
-                "apps",
-                key,
-                ModMask::default(),
-                KeyAction::Spawn(FILEMANAGER_APP.to_string()),
-            );
-        }
+    /// Enter a submap, grab its keys and notify IPC subscribers
+    pub fn enter_mode(&mut self, mode: String) -> Result<()> {
+        let previous = self.keybindings.current_mode.clone();
+        self.keybindings.enter_mode(mode);
+        self.update_grabs()?;
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_ESCAPE) {
-            self.keybindings
-                .bind_in_mode("apps", key, ModMask::default(), KeyAction::ExitMode);
+        if self.keybindings.current_mode != previous {
+            let mode = self.keybindings.current_mode.clone();
+            self.emit("mode", json!({ "mode": mode }));
         }
+        Ok(())
+    }
 
-        // === SUBMAP: ALERTS (oneshot) ===
-        self.keybindings.add_submap("alerts".to_string(), true);
+    /// Go back to normal mode, regrab its keys and notify IPC subscribers
+    pub fn exit_mode(&mut self) -> Result<()> {
+        let was_in_submap = self.keybindings.is_in_submap();
+        self.keybindings.exit_mode();
+        self.update_grabs()?;
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_L) {
-            self.keybindings.bind_in_mode(
-                "alerts",
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|_| {
-                    launch_dmenu();
-                }),
-            );
+        if was_in_submap {
+            self.emit("mode", json!({ "mode": null }));
         }
+        Ok(())
+    }
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_B) {
-            self.keybindings.bind_in_mode(
-                "alerts",
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    let percentage =
-                        get_command_output("cat /sys/class/power_supply/BAT0/capacity");
-                    let bat_state = get_command_output("cat /sys/class/power_supply/BAT0/status");
-                    let bat_state = bat_state.trim();
-
-                    let label = match bat_state {
-                        "Charging" => "CHR",
-                        "Discharging" => "BAT",
-                        "Full" => "FULL",
-                        _ => "UNK",
-                    };
-
-                    wm.draw_alert(format!("[{}] {}%", label, percentage.trim()))
-                        .ok();
-                }),
-            );
-        }
+    /// Bind a key in normal mode at runtime and grab it
+    pub fn bind_key(&mut self, keysym: u32, modifiers: ModMask, action: KeyAction) -> Result<()> {
+        self.bind_keysym(keysym, modifiers, action.clone())?;
+        self.ipc_bindings.push((keysym, modifiers, action));
+        self.update_grabs()
+    }
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_V) {
-            self.keybindings.bind_in_mode(
-                "alerts",
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    let percentage = get_command_output("pamixer --get-volume");
-                    let status = if is_muted() { "MUTED" } else { "VOL" };
-
-                    wm.draw_alert(format!("[{}] {}%", status, percentage.trim()))
-                        .ok();
-                }),
-            );
+    /// Put bindings added over IPC back on top of a freshly rebuilt config
+    pub fn restore_ipc_bindings(&mut self) -> Result<()> {
+        for (keysym, modifiers, action) in self.ipc_bindings.clone() {
+            if let Err(e) = self.bind_keysym(keysym, modifiers, action) {
+                eprintln!("Failed to restore binding: {}", e);
+            }
         }
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_D) {
-            self.keybindings.bind_in_mode(
-                "alerts",
-                key,
-                ModMask::default(),
-                KeyAction::Custom(|wm| {
-                    let date = get_command_output("date '+%a %d %b %H:%M'");
+        self.update_grabs()
+    }
 
-                    wm.draw_alert(format!("[DATE] {}", date.trim())).ok();
-                }),
-            );
-        }
+    fn bind_keysym(&mut self, keysym: u32, modifiers: ModMask, action: KeyAction) -> Result<()> {
+        let setup = self.conn.setup();
+        let screen = &setup.roots[self.screen_num];
+        let grabber = KeyboardGrabber::new(&self.conn, screen, setup)?;
 
-        if let Some(key) = grabber.keysym_to_keycode(XK_ESCAPE) {
-            self.keybindings
-                .bind_in_mode("alerts", key, ModMask::default(), KeyAction::ExitMode);
-        }
+        let key = grabber.keysym_to_keycode(keysym).ok_or_else(|| {
+            anyhow::anyhow!("No keycode for `{}`", format_key_chord(keysym, modifiers))
+        })?;
 
-        self.update_grabs()?;
+        self.keybindings.bind_normal(key, modifiers, action);
         Ok(())
     }
 
@@ -588,7 +150,7 @@ impl WindowManager {
         Ok(())
     }
 
-    fn execute_action(&mut self, action: KeyAction) -> Result<()> {
+    pub fn execute_action(&mut self, action: KeyAction) -> Result<()> {
         match action {
             KeyAction::Spawn(cmd) => {
                 println!("▶ Spawning: {}", cmd);
@@ -596,13 +158,11 @@ impl WindowManager {
             }
             KeyAction::EnterMode(mode) => {
                 self.draw_alert(format!("[MODE] {}", mode.to_ascii_uppercase()))?;
-                self.keybindings.enter_mode(mode);
-                self.update_grabs()?;
+                self.enter_mode(mode)?;
             }
             KeyAction::ExitMode => {
                 self.draw_alert("[MODE] NORMAL".to_string())?;
-                self.keybindings.exit_mode();
-                self.update_grabs()?;
+                self.exit_mode()?;
             }
             KeyAction::CloseWindow => {
                 if let Err(err) = self.close_focused_client() {
@@ -615,6 +175,113 @@ impl WindowManager {
             KeyAction::FocusPrev => {
                 self.focus_prev()?;
             }
+            KeyAction::SwapNext => {
+                self.swap_next()?;
+            }
+            KeyAction::SwapPrev => {
+                self.swap_prev()?;
+            }
+            KeyAction::PromoteToMaster => {
+                self.promote_to_master()?;
+            }
+            KeyAction::RotateWindows => {
+                self.rotate_windows()?;
+            }
+            KeyAction::ToggleFullscreen => {
+                if let Some(window) = self.focused_client() {
+                    self.toggle_fullscreen(window)?;
+                }
+            }
+            KeyAction::ToggleFloating => {
+                if let Some(window) = self.focused_client() {
+                    self.toggle_floating(window)?;
+                }
+            }
+            KeyAction::MoveFloating(direction) => {
+                if let Some(window) = self.focused_client() {
+                    self.move_floating(window, direction)?;
+                }
+            }
+            KeyAction::ResizeFloating(direction) => {
+                if let Some(window) = self.focused_client() {
+                    self.resize_floating(window, direction)?;
+                }
+            }
+            KeyAction::CenterFloating => {
+                if let Some(window) = self.focused_client() {
+                    self.center_floating(window)?;
+                }
+            }
+            KeyAction::NextLayout => {
+                self.next_layout()?;
+                self.draw_alert(format!(
+                    "[LAY] {}",
+                    self.monitors
+                        .current()
+                        .workspaces
+                        .current()
+                        .layout_config
+                        .current
+                ))?;
+            }
+            KeyAction::SetLayout(layout) => {
+                self.set_layout(&layout)?;
+                self.draw_alert(format!("[LAY] {}", layout))?;
+            }
+            KeyAction::IncreaseMasterRatio => {
+                self.increase_master_ratio()?;
+            }
+            KeyAction::DecreaseMasterRatio => {
+                self.decrease_master_ratio()?;
+            }
+            KeyAction::IncreaseNMaster => {
+                self.increase_nmaster()?;
+            }
+            KeyAction::DecreaseNMaster => {
+                self.decrease_nmaster()?;
+            }
+            KeyAction::IncreaseGap => {
+                self.increase_gap()?;
+            }
+            KeyAction::DecreaseGap => {
+                self.decrease_gap()?;
+            }
+            KeyAction::CycleLastWorkspace => {
+                self.cycle_last_workspace()?;
+            }
+            KeyAction::FocusNextMonitor => {
+                self.focus_next_monitor()?;
+            }
+            KeyAction::FocusPrevMonitor => {
+                self.focus_prev_monitor()?;
+            }
+            KeyAction::FocusMonitor(id) => {
+                self.focus_monitor(id)?;
+            }
+            KeyAction::MoveToNextMonitor => {
+                self.move_focused_to_next_monitor()?;
+            }
+            KeyAction::MoveToPrevMonitor => {
+                self.move_focused_to_prev_monitor()?;
+            }
+            KeyAction::MoveToMonitor(id) => {
+                self.move_focused_to_monitor(id)?;
+            }
+            KeyAction::RefreshMonitors => {
+                println!("🔄 Manually refreshing monitors...");
+                let changes = self.monitors.refresh(&self.conn, self.root)?;
+                self.handle_monitor_changes(changes)?;
+                self.draw_alert("Monitors refreshed".to_string())?;
+            }
+            KeyAction::Banish => {
+                self.banish()?;
+            }
+            KeyAction::ClearAlerts => {
+                self.clear_alerts()?;
+            }
+            KeyAction::Reload => {
+                self.reload_config()?;
+            }
             KeyAction::Custom(func) => {
                 func(self);
             }
@@ -629,6 +296,8 @@ impl WindowManager {
                 }
             }
             KeyAction::Quit => {
+                // drop the IPC server so its socket is removed
+                self.ipc = None;
                 exit(200);
             }
         }
@@ -646,15 +315,13 @@ impl WindowManager {
             self.execute_action(action)?;
 
             if should_exit {
-                self.keybindings.exit_mode();
-                self.update_grabs()?;
+                self.exit_mode()?;
             }
         } else {
             if self.keybindings.is_in_submap() {
                 println!("✗ Key not mapped in current mode, exiting...");
                 self.draw_alert("[ERR] KEY".to_string())?;
-                self.keybindings.exit_mode();
-                self.update_grabs()?;
+                self.exit_mode()?;
             } else {
                 println!("✗ Key not mapped");
             }
@@ -663,3 +330,224 @@ impl WindowManager {
         Ok(())
     }
 }
+
+/// Built-in bindings as `(submap, chord, action)`, `None` being normal mode
+fn default_bindings(config: &Config) -> Vec<(Option<&'static str>, String, KeyAction)> {
+    let mut bindings = Vec::new();
+
+    // === MODO NORMAL ===
+    for i in 1..=config.workspaces.min(9) {
+        bindings.push((None, format!("Super+{}", i), KeyAction::SwitchWorkspace(i)));
+
+        // Super+Shift+1-9: Mover ventana a workspace
+        bindings.push((
+            None,
+            format!("Super+Shift+{}", i),
+            KeyAction::MoveToWorkspace(i),
+        ));
+    }
+
+    let normal = [
+        // Cambiar al monitor anterior / siguiente
+        ("Super+comma", KeyAction::FocusPrevMonitor),
+        ("Super+period", KeyAction::FocusNextMonitor),
+        // Mover ventana al monitor anterior / siguiente
+        ("Super+Shift+comma", KeyAction::MoveToPrevMonitor),
+        ("Super+Shift+period", KeyAction::MoveToNextMonitor),
+        ("Super+Shift+Escape", KeyAction::Quit),
+        (
+            "Super+Return",
+            KeyAction::Spawn(config.apps.terminal.clone()),
+        ),
+        ("Super+Shift+c", KeyAction::CloseWindow),
+        ("Super+n", KeyAction::EnterMode("nav".to_string())),
+        ("Super+a", KeyAction::EnterMode("apps".to_string())),
+        ("Super+s", KeyAction::EnterMode("alerts".to_string())),
+        ("Super+r", KeyAction::EnterMode("resize".to_string())),
+        ("Super+space", KeyAction::NextLayout),
+        ("Super+Shift+space", KeyAction::ToggleFloating),
+        ("Super+j", KeyAction::FocusNext),
+        ("Super+k", KeyAction::FocusPrev),
+        ("Super+Shift+j", KeyAction::SwapNext),
+        ("Super+Shift+k", KeyAction::SwapPrev),
+        ("Super+h", KeyAction::DecreaseMasterRatio),
+        ("Super+l", KeyAction::IncreaseMasterRatio),
+        ("Super+i", KeyAction::IncreaseNMaster),
+        ("Super+d", KeyAction::DecreaseNMaster),
+        ("Super+b", KeyAction::Banish),
+        ("Super+f", KeyAction::ToggleFullscreen),
+        ("Super+g", KeyAction::ClearAlerts),
+        ("Super+Shift+r", KeyAction::RefreshMonitors),
+        ("Super+Ctrl+r", KeyAction::Reload),
+        ("Super+Tab", KeyAction::CycleLastWorkspace),
+        ("Super+Shift+Return", KeyAction::PromoteToMaster),
+        ("Super+Shift+equal", KeyAction::IncreaseGap),
+        ("Super+minus", KeyAction::DecreaseGap),
+        (
+            "XF86AudioRaiseVolume",
+            KeyAction::Custom(|wm| {
+                get_command_output("pactl set-sink-volume @DEFAULT_SINK@ +10%");
+                let percentage = get_volume();
+                let status = if is_muted() { "MUTED" } else { "VOL" };
+
+                wm.draw_alert(format!("[{}] {}%", status, percentage)).ok();
+            }),
+        ),
+        (
+            "XF86AudioLowerVolume",
+            KeyAction::Custom(|wm| {
+                get_command_output("pactl set-sink-volume @DEFAULT_SINK@ -10%");
+                let percentage = get_volume();
+                let status = if is_muted() { "MUTED" } else { "VOL" };
+
+                wm.draw_alert(format!("[{}] {}%", status, percentage)).ok();
+            }),
+        ),
+        (
+            "XF86AudioMute",
+            KeyAction::Custom(|wm| {
+                let _ = get_command_output("pamixer --toggle-mute");
+                let percentage = get_volume();
+                let status = if is_muted() { "MUTED" } else { "VOL" };
+
+                wm.draw_alert(format!("[{}] {}%", status, percentage)).ok();
+            }),
+        ),
+        (
+            "XF86AudioMicMute",
+            KeyAction::Custom(|wm| {
+                let _ = get_command_output("pamixer --default-source --toggle-mute");
+
+                let muted =
+                    get_command_output("pamixer --default-source --get-mute").trim() == "true";
+
+                let msg = if muted { "[MIC] MUTED" } else { "[MIC] LIVE" };
+                wm.draw_alert(msg.to_string()).ok();
+            }),
+        ),
+        (
+            "Print",
+            KeyAction::Custom(|wm| {
+                Command::new("sh")
+                    .arg("-c")
+                    .arg("maim | xclip -selection clipboard -t image/png -i")
+                    .spawn()
+                    .unwrap()
+                    .wait()
+                    .ok();
+
+                wm.draw_alert("[SCR] FULL".to_string()).ok();
+            }),
+        ),
+        (
+            "Shift+Print",
+            KeyAction::Custom(|wm| {
+                Command::new("sh")
+                    .arg("-c")
+                    .arg("maim -s | xclip -selection clipboard -t image/png -i")
+                    .spawn()
+                    .unwrap()
+                    .wait()
+                    .ok();
+
+                wm.draw_alert("[SCR] AREA".to_string()).ok();
+            }),
+        ),
+    ];
+
+    // === SUBMAP: NAV ===
+    let nav = [
+        ("j", KeyAction::FocusNext),
+        ("k", KeyAction::FocusPrev),
+        ("Escape", KeyAction::ExitMode),
+    ];
+
+    // === SUBMAP: RESIZE ===
+    let resize = [
+        ("h", KeyAction::MoveFloating(Direction::Left)),
+        ("j", KeyAction::MoveFloating(Direction::Down)),
+        ("k", KeyAction::MoveFloating(Direction::Up)),
+        ("l", KeyAction::MoveFloating(Direction::Right)),
+        ("Shift+h", KeyAction::ResizeFloating(Direction::Left)),
+        ("Shift+j", KeyAction::ResizeFloating(Direction::Down)),
+        ("Shift+k", KeyAction::ResizeFloating(Direction::Up)),
+        ("Shift+l", KeyAction::ResizeFloating(Direction::Right)),
+        ("c", KeyAction::CenterFloating),
+        ("Escape", KeyAction::ExitMode),
+    ];
+
+    // === SUBMAP: APPS (oneshot) ===
+    let apps = [
+        ("t", KeyAction::Spawn(config.apps.terminal.clone())),
+        (
+            "e",
+            KeyAction::Custom(|wm| {
+                Command::new("emacsclient").arg("-c").spawn().ok();
+                wm.draw_alert("[EMACS] SPAWN".to_string()).ok(); // TODO: remove???
+            }),
+        ),
+        ("f", KeyAction::Spawn(config.apps.file_manager.clone())),
+        ("Escape", KeyAction::ExitMode),
+    ];
+
+    // === SUBMAP: ALERTS (oneshot) ===
+    let alerts = [
+        (
+            "l",
+            KeyAction::Custom(|wm| {
+                launch_dmenu(&wm.config.appearance);
+            }),
+        ),
+        (
+            "b",
+            KeyAction::Custom(|wm| {
+                let percentage = get_command_output("cat /sys/class/power_supply/BAT0/capacity");
+                let bat_state = get_command_output("cat /sys/class/power_supply/BAT0/status");
+                let bat_state = bat_state.trim();
+
+                let label = match bat_state {
+                    "Charging" => "CHR",
+                    "Discharging" => "BAT",
+                    "Full" => "FULL",
+                    _ => "UNK",
+                };
+
+                wm.draw_alert(format!("[{}] {}%", label, percentage.trim()))
+                    .ok();
+            }),
+        ),
+        (
+            "v",
+            KeyAction::Custom(|wm| {
+                let percentage = get_command_output("pamixer --get-volume");
+                let status = if is_muted() { "MUTED" } else { "VOL" };
+
+                wm.draw_alert(format!("[{}] {}%", status, percentage.trim()))
+                    .ok();
+            }),
+        ),
+        (
+            "d",
+            KeyAction::Custom(|wm| {
+                let date = get_command_output("date '+%a %d %b %H:%M'");
+
+                wm.draw_alert(format!("[DATE] {}", date.trim())).ok();
+            }),
+        ),
+        ("Escape", KeyAction::ExitMode),
+    ];
+
+    for (mode, entries) in [
+        (None, Vec::from(normal)),
+        (Some("nav"), Vec::from(nav)),
+        (Some("resize"), Vec::from(resize)),
+        (Some("apps"), Vec::from(apps)),
+        (Some("alerts"), Vec::from(alerts)),
+    ] {
+        for (chord, action) in entries {
+            bindings.push((mode, chord.to_string(), action));
+        }
+    }
+
+    bindings
+}
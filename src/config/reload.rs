@@ -0,0 +1,75 @@
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+
+use crate::config::config::Config;
+use crate::keybindings::KeyBindingManager;
+use crate::wm::WindowManager;
+
+impl WindowManager {
+    /// Re-read the config file and apply it without dropping any client.
+    ///
+    /// A config that fails to parse is reported and the current one is kept.
+    pub fn reload_config(&mut self) -> Result<()> {
+        let mut config = match Config::load() {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("Error reloading config, keeping the current one: {}", err);
+                self.draw_alert(format!("[CFG] {}", err))?;
+                return Ok(());
+            }
+        };
+
+        // the workspaces already hold clients, so their count is fixed until restart
+        if config.workspaces != self.monitors.num_workspaces {
+            eprintln!("Changing the number of workspaces requires a restart");
+            self.draw_alert("[CFG] RESTART FOR WORKSPACES".to_string())?;
+            config.workspaces = self.monitors.num_workspaces;
+        }
+
+        println!("Reloading config");
+
+        self.border_width = config.appearance.border_width;
+        self.border_focused_color = config.appearance.border_focused;
+        self.border_unfocused_color = config.appearance.border_unfocused;
+
+        // only what changed in the file overrides what was adjusted at runtime
+        self.monitors.layout_config = config.layout.clone();
+        for monitor in &mut self.monitors.monitors {
+            for workspace in &mut monitor.workspaces.workspaces {
+                workspace
+                    .layout_config
+                    .apply_changes(&self.config.layout, &config.layout);
+
+                for (&window, state) in &workspace.clients {
+                    if !state.is_fullscreen {
+                        self.conn.configure_window(
+                            window,
+                            &ConfigureWindowAux::new().border_width(self.border_width),
+                        )?;
+                    }
+                }
+            }
+        }
+
+        self.config = config;
+
+        self.keybindings = KeyBindingManager::new();
+        self.setup_keybindings()?;
+        self.restore_ipc_bindings()?;
+
+        let saved_monitor = self.monitors.current_monitor;
+        for monitor_id in 0..self.monitors.count() {
+            self.monitors.switch_to(monitor_id);
+            self.update_client_borders()?;
+        }
+        self.monitors.switch_to(saved_monitor);
+
+        self.layout_all_monitors()?;
+
+        self.draw_alert("[CFG] RELOADED".to_string())?;
+
+        self.conn.flush()?;
+        Ok(())
+    }
+}
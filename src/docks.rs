@@ -0,0 +1,231 @@
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+
+use crate::layout::Rect;
+use crate::wm::WindowManager;
+
+/// Space a dock reserves along the root window edges, `_NET_WM_STRUT_PARTIAL` layout
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Strut {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+    pub left_start_y: u32,
+    pub left_end_y: u32,
+    pub right_start_y: u32,
+    pub right_end_y: u32,
+    pub top_start_x: u32,
+    pub top_end_x: u32,
+    pub bottom_start_x: u32,
+    pub bottom_end_x: u32,
+}
+
+impl Strut {
+    /// Build from `_NET_WM_STRUT_PARTIAL` (12 values) or `_NET_WM_STRUT` (4 values)
+    fn from_values(values: &[u32]) -> Option<Self> {
+        match *values {
+            [left, right, top, bottom, ls, le, rs, re, ts, te, bs, be, ..] => Some(Self {
+                left,
+                right,
+                top,
+                bottom,
+                left_start_y: ls,
+                left_end_y: le,
+                right_start_y: rs,
+                right_end_y: re,
+                top_start_x: ts,
+                top_end_x: te,
+                bottom_start_x: bs,
+                bottom_end_x: be,
+            }),
+            // a plain strut spans the whole edge
+            [left, right, top, bottom, ..] => Some(Self {
+                left,
+                right,
+                top,
+                bottom,
+                left_end_y: u32::MAX,
+                right_end_y: u32::MAX,
+                top_end_x: u32::MAX,
+                bottom_end_x: u32::MAX,
+                ..Self::default()
+            }),
+            _ => None,
+        }
+    }
+
+    /// Left, right, top and bottom insets this strut cuts out of a monitor
+    fn insets(&self, monitor: Rect, root_width: u32, root_height: u32) -> [u32; 4] {
+        let x = monitor.x.max(0) as u32;
+        let y = monitor.y.max(0) as u32;
+        let width = monitor.width as u32;
+        let height = monitor.height as u32;
+
+        let spans_x = |start: u32, end: u32| start < x + width && end >= x;
+        let spans_y = |start: u32, end: u32| start < y + height && end >= y;
+
+        let mut insets = [0; 4];
+
+        if self.left > 0 && spans_y(self.left_start_y, self.left_end_y) {
+            insets[0] = self.left.saturating_sub(x).min(width);
+        }
+        if self.right > 0 && spans_y(self.right_start_y, self.right_end_y) {
+            let edge = root_width.saturating_sub(self.right);
+            insets[1] = (x + width).saturating_sub(edge).min(width);
+        }
+        if self.top > 0 && spans_x(self.top_start_x, self.top_end_x) {
+            insets[2] = self.top.saturating_sub(y).min(height);
+        }
+        if self.bottom > 0 && spans_x(self.bottom_start_x, self.bottom_end_x) {
+            let edge = root_height.saturating_sub(self.bottom);
+            insets[3] = (y + height).saturating_sub(edge).min(height);
+        }
+
+        insets
+    }
+}
+
+impl WindowManager {
+    /// Check for `_NET_WM_WINDOW_TYPE_DOCK`
+    pub fn is_dock(&self, window: Window) -> Result<bool> {
+        Ok(self
+            .window_types(window)?
+            .contains(&self.atoms.net_wm_window_type_dock))
+    }
+
+    /// Map a dock without tiling or focusing it and reserve its struts
+    pub fn manage_dock(&mut self, window: Window) -> Result<()> {
+        println!("Managing dock: {}", window);
+
+        self.conn.change_window_attributes(
+            window,
+            &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )?;
+
+        let strut = self.read_strut(window)?;
+        self.docks.insert(window, strut);
+
+        self.conn.map_window(window)?;
+
+        self.update_workarea()?;
+        self.layout_all_monitors()?;
+        self.restack()?;
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Forget a dock and give its reserved space back to the layouts
+    pub fn unmanage_dock(&mut self, window: Window) -> Result<bool> {
+        if self.docks.remove(&window).is_none() {
+            return Ok(false);
+        }
+
+        println!("Unmanaging dock: {}", window);
+
+        self.update_workarea()?;
+        self.layout_all_monitors()?;
+
+        self.conn.flush()?;
+        Ok(true)
+    }
+
+    /// Re-read the struts of a dock after it changed them
+    pub fn update_dock_strut(&mut self, window: Window) -> Result<()> {
+        let strut = self.read_strut(window)?;
+
+        if self.docks.get(&window) == Some(&strut) {
+            return Ok(());
+        }
+        self.docks.insert(window, strut);
+
+        self.update_workarea()?;
+        self.layout_all_monitors()?;
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Check for `_NET_WM_WINDOW_TYPE_DESKTOP`
+    pub fn is_desktop_window(&self, window: Window) -> Result<bool> {
+        Ok(self
+            .window_types(window)?
+            .contains(&self.atoms.net_wm_window_type_desktop))
+    }
+
+    /// Map a desktop window, e.g. a file manager's icons, under everything else
+    pub fn manage_desktop_window(&mut self, window: Window) -> Result<()> {
+        if self.desktop_windows.contains(&window) {
+            return Ok(());
+        }
+
+        println!("Managing desktop window: {}", window);
+
+        self.desktop_windows.push(window);
+        self.conn.map_window(window)?;
+
+        self.restack()
+    }
+
+    /// Forget a desktop window, returns whether it was one
+    pub fn unmanage_desktop_window(&mut self, window: Window) -> Result<bool> {
+        let Some(index) = self.desktop_windows.iter().position(|&w| w == window) else {
+            return Ok(false);
+        };
+
+        println!("Unmanaging desktop window: {}", window);
+
+        self.desktop_windows.remove(index);
+        Ok(true)
+    }
+
+    fn read_strut(&self, window: Window) -> Result<Strut> {
+        for property in [self.atoms.net_wm_strut_partial, self.atoms.net_wm_strut] {
+            let reply = self
+                .conn
+                .get_property(false, window, property, AtomEnum::CARDINAL, 0, 12)?
+                .reply()?;
+
+            let values: Vec<u32> = reply.value32().into_iter().flatten().collect();
+            if let Some(strut) = Strut::from_values(&values) {
+                return Ok(strut);
+            }
+        }
+
+        Ok(Strut::default())
+    }
+
+    /// Recompute every monitor's work area from the dock struts
+    pub fn update_workarea(&mut self) -> Result<()> {
+        let root = self.conn.get_geometry(self.root)?.reply()?;
+
+        for monitor in &mut self.monitors.monitors {
+            let geometry = Rect {
+                x: monitor.x,
+                y: monitor.y,
+                width: monitor.width,
+                height: monitor.height,
+            };
+
+            let mut reserved = [0; 4];
+            for strut in self.docks.values() {
+                let insets = strut.insets(geometry, root.width as u32, root.height as u32);
+                for (side, inset) in reserved.iter_mut().zip(insets) {
+                    *side = (*side).max(inset);
+                }
+            }
+
+            let [left, right, top, bottom] = reserved;
+            monitor.workarea = Rect {
+                x: monitor.x + left as i16,
+                y: monitor.y + top as i16,
+                width: (monitor.width as u32).saturating_sub(left + right) as u16,
+                height: (monitor.height as u32).saturating_sub(top + bottom) as u16,
+            };
+        }
+
+        Ok(())
+    }
+}
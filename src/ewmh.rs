@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde_json::json;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::COPY_DEPTH_FROM_PARENT;
+
+use crate::wm::WindowManager;
+
+/// Root properties as last published, so unchanged ones are not rewritten
+#[derive(Debug, Default)]
+pub struct EwmhState {
+    pub check_window: Window,
+    number_of_desktops: Option<u32>,
+    desktop_names: Option<Vec<String>>,
+    desktop_viewport: Option<Vec<u32>>,
+    workarea: Option<Vec<u32>>,
+    current_desktop: Option<u32>,
+    client_list: Option<Vec<Window>>,
+    client_list_stacking: Option<Vec<Window>>,
+    active_window: Option<Window>,
+    client_desktops: HashMap<Window, u32>,
+}
+
+impl WindowManager {
+    /// Create the supporting WM check window and publish the initial root properties
+    pub fn setup_ewmh(&mut self) -> Result<()> {
+        let check_window = self.conn.generate_id()?;
+
+        self.conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            check_window,
+            self.root,
+            -1,
+            -1,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::default().override_redirect(1),
+        )?;
+
+        for window in [self.root, check_window] {
+            self.conn.change_property32(
+                PropMode::REPLACE,
+                window,
+                self.atoms.net_supporting_wm_check,
+                AtomEnum::WINDOW,
+                &[check_window],
+            )?;
+        }
+
+        self.conn.change_property8(
+            PropMode::REPLACE,
+            check_window,
+            self.atoms.net_wm_name,
+            self.atoms.utf8_string,
+            b"dxwm",
+        )?;
+
+        let supported = [
+            self.atoms.net_supported,
+            self.atoms.net_supporting_wm_check,
+            self.atoms.net_wm_name,
+            self.atoms.net_number_of_desktops,
+            self.atoms.net_current_desktop,
+            self.atoms.net_desktop_names,
+            self.atoms.net_desktop_viewport,
+            self.atoms.net_client_list,
+            self.atoms.net_client_list_stacking,
+            self.atoms.net_active_window,
+            self.atoms.net_wm_desktop,
+            self.atoms.net_close_window,
+            self.atoms.net_moveresize_window,
+            self.atoms.net_wm_state,
+            self.atoms.net_wm_state_fullscreen,
+            self.atoms.net_wm_state_demands_attention,
+            self.atoms.net_wm_state_above,
+            self.atoms.net_wm_state_below,
+            self.atoms.net_wm_window_type,
+            self.atoms.net_wm_window_type_dock,
+            self.atoms.net_wm_window_type_dialog,
+            self.atoms.net_wm_window_type_utility,
+            self.atoms.net_wm_window_type_splash,
+            self.atoms.net_wm_window_type_toolbar,
+            self.atoms.net_wm_window_type_desktop,
+            self.atoms.net_wm_strut,
+            self.atoms.net_wm_strut_partial,
+            self.atoms.net_workarea,
+        ];
+
+        self.conn.change_property32(
+            PropMode::REPLACE,
+            self.root,
+            self.atoms.net_supported,
+            AtomEnum::ATOM,
+            &supported,
+        )?;
+
+        self.ewmh.check_window = check_window;
+        self.update_ewmh()
+    }
+
+    /// Flattened desktop index of a workspace: `monitor * workspaces + (workspace - 1)`
+    pub fn desktop_index(&self, monitor: usize, workspace: u8) -> u32 {
+        monitor as u32 * self.monitors.num_workspaces as u32 + workspace as u32 - 1
+    }
+
+    /// Inverse of `desktop_index`, returns the monitor and workspace id
+    pub fn desktop_location(&self, desktop: u32) -> Option<(usize, u8)> {
+        let per_monitor = self.monitors.num_workspaces as u32;
+        let monitor = (desktop / per_monitor) as usize;
+
+        if monitor >= self.monitors.count() {
+            return None;
+        }
+        Some((monitor, (desktop % per_monitor) as u8 + 1))
+    }
+
+    /// The `_NET_WM_WINDOW_TYPE` list of a window
+    pub fn window_types(&self, window: Window) -> Result<Vec<Atom>> {
+        let reply = self
+            .conn
+            .get_property(
+                false,
+                window,
+                self.atoms.net_wm_window_type,
+                AtomEnum::ATOM,
+                0,
+                32,
+            )?
+            .reply()?;
+
+        Ok(reply.value32().into_iter().flatten().collect())
+    }
+
+    /// Publish a client's `_NET_WM_STATE` from its ClientState
+    pub fn update_net_wm_state(&self, window: Window) -> Result<()> {
+        let Some(state) = self.find_client(window) else {
+            return Ok(());
+        };
+
+        let mut atoms = Vec::new();
+        if state.is_fullscreen {
+            atoms.push(self.atoms.net_wm_state_fullscreen);
+        }
+        if state.is_urgent {
+            atoms.push(self.atoms.net_wm_state_demands_attention);
+        }
+        if state.is_above {
+            atoms.push(self.atoms.net_wm_state_above);
+        }
+        if state.is_below {
+            atoms.push(self.atoms.net_wm_state_below);
+        }
+
+        self.conn.change_property32(
+            PropMode::REPLACE,
+            window,
+            self.atoms.net_wm_state,
+            AtomEnum::ATOM,
+            &atoms,
+        )?;
+        Ok(())
+    }
+
+    /// Workspace a window asks to be placed on through `_NET_WM_DESKTOP`
+    pub fn requested_desktop(&self, window: Window) -> Result<Option<(usize, u8)>> {
+        let reply = self
+            .conn
+            .get_property(
+                false,
+                window,
+                self.atoms.net_wm_desktop,
+                AtomEnum::CARDINAL,
+                0,
+                1,
+            )?
+            .reply()?;
+
+        Ok(reply
+            .value32()
+            .and_then(|mut values| values.next())
+            .and_then(|desktop| self.desktop_location(desktop)))
+    }
+
+    /// Sync the EWMH root properties with the workspace and client model
+    pub fn update_ewmh(&mut self) -> Result<()> {
+        let mut names = Vec::new();
+        let mut viewport = Vec::new();
+        let mut workarea = Vec::new();
+        let mut client_desktops = HashMap::new();
+
+        for monitor in &self.monitors.monitors {
+            for workspace in &monitor.workspaces.workspaces {
+                names.push(workspace.name.clone());
+                viewport.extend([monitor.x as u32, monitor.y as u32]);
+
+                let area = monitor.workarea;
+                workarea.extend([
+                    area.x as u32,
+                    area.y as u32,
+                    area.width as u32,
+                    area.height as u32,
+                ]);
+
+                let desktop = self.desktop_index(monitor.id, workspace.id);
+                for &window in &workspace.clients_order {
+                    client_desktops.insert(window, desktop);
+                }
+            }
+        }
+
+        let client_list = self.managed.clone();
+
+        let monitor = self.monitors.current();
+        let current = self.desktop_index(monitor.id, monitor.workspaces.current_workspace);
+
+        // the X server keeps children in stacking order, bottom to top
+        let stacking: Vec<Window> = self
+            .conn
+            .query_tree(self.root)?
+            .reply()?
+            .children
+            .into_iter()
+            .filter(|w| client_list.contains(w))
+            .collect();
+
+        let active = self.focused_client().unwrap_or(x11rb::NONE);
+
+        if self.ewmh.number_of_desktops != Some(names.len() as u32) {
+            self.set_root_cardinals(self.atoms.net_number_of_desktops, &[names.len() as u32])?;
+            self.ewmh.number_of_desktops = Some(names.len() as u32);
+        }
+
+        if self.ewmh.desktop_names.as_ref() != Some(&names) {
+            let mut value = Vec::new();
+            for name in &names {
+                value.extend_from_slice(name.as_bytes());
+                value.push(0);
+            }
+
+            self.conn.change_property8(
+                PropMode::REPLACE,
+                self.root,
+                self.atoms.net_desktop_names,
+                self.atoms.utf8_string,
+                &value,
+            )?;
+            self.ewmh.desktop_names = Some(names);
+        }
+
+        if self.ewmh.desktop_viewport.as_ref() != Some(&viewport) {
+            self.set_root_cardinals(self.atoms.net_desktop_viewport, &viewport)?;
+            self.ewmh.desktop_viewport = Some(viewport);
+        }
+
+        if self.ewmh.workarea.as_ref() != Some(&workarea) {
+            self.set_root_cardinals(self.atoms.net_workarea, &workarea)?;
+            self.ewmh.workarea = Some(workarea);
+        }
+
+        if self.ewmh.current_desktop != Some(current) {
+            self.set_root_cardinals(self.atoms.net_current_desktop, &[current])?;
+            self.ewmh.current_desktop = Some(current);
+        }
+
+        if self.ewmh.client_list.as_ref() != Some(&client_list) {
+            self.set_root_windows(self.atoms.net_client_list, &client_list)?;
+            self.ewmh.client_list = Some(client_list);
+        }
+
+        for (&window, &desktop) in &client_desktops {
+            if self.ewmh.client_desktops.get(&window) != Some(&desktop) {
+                self.conn.change_property32(
+                    PropMode::REPLACE,
+                    window,
+                    self.atoms.net_wm_desktop,
+                    AtomEnum::CARDINAL,
+                    &[desktop],
+                )?;
+            }
+        }
+        self.ewmh.client_desktops = client_desktops;
+
+        if self.ewmh.client_list_stacking.as_ref() != Some(&stacking) {
+            self.set_root_windows(self.atoms.net_client_list_stacking, &stacking)?;
+            self.ewmh.client_list_stacking = Some(stacking);
+        }
+
+        if self.ewmh.active_window != Some(active) {
+            self.set_root_windows(self.atoms.net_active_window, &[active])?;
+            self.ewmh.active_window = Some(active);
+        }
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Act on the EWMH requests pagers, taskbars and applications send to the root window
+    pub fn handle_client_message(&mut self, e: ClientMessageEvent) -> Result<()> {
+        let data = e.data.as_data32();
+
+        if e.type_ == self.atoms.net_wm_state {
+            self.handle_state_request(e.window, data[0], data[1], data[2])?;
+        } else if e.type_ == self.atoms.net_active_window {
+            self.activate_client(e.window)?;
+        } else if e.type_ == self.atoms.net_current_desktop {
+            if let Some((monitor, workspace)) = self.desktop_location(data[0]) {
+                self.switch_to_desktop(monitor, workspace)?;
+            }
+        } else if e.type_ == self.atoms.net_close_window {
+            if self.locate_client(e.window).is_some() {
+                self.close_client(e.window)?;
+            }
+        } else if e.type_ == self.atoms.net_wm_desktop {
+            // 0xFFFFFFFF asks for every desktop, which dxwm has no notion of
+            if let Some((monitor, workspace)) = self.desktop_location(data[0]) {
+                self.move_client(e.window, monitor, workspace)?;
+            }
+        } else if e.type_ == self.atoms.net_moveresize_window {
+            self.handle_moveresize_request(e.window, data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Bring a client into view wherever it lives and focus it
+    pub fn activate_client(&mut self, window: Window) -> Result<()> {
+        let Some((monitor, workspace)) = self.locate_client(window) else {
+            return Ok(());
+        };
+
+        self.switch_to_desktop(monitor, workspace)?;
+        self.focus_client(window)
+    }
+
+    /// Switch to a workspace of any monitor without warping the pointer
+    fn switch_to_desktop(&mut self, monitor: usize, workspace: u8) -> Result<()> {
+        let monitor_changed =
+            self.monitors.current_monitor != monitor && self.monitors.switch_to(monitor);
+
+        if !self.switch_to_workspace(workspace)? && monitor_changed {
+            if let Some(focused) = self.focused_client() {
+                self.give_input_focus(focused)?;
+            }
+            self.update_client_borders()?;
+
+            self.emit(
+                "workspace",
+                json!({ "monitor": monitor, "workspace": workspace }),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Apply `_NET_MOVERESIZE_WINDOW`, tiled clients keep the geometry of their layout
+    fn handle_moveresize_request(&mut self, window: Window, data: [u32; 5]) -> Result<()> {
+        // bits 8-11 flag which of x, y, width and height are present
+        let has = |bit: u32| data[0] & (1 << bit) != 0;
+
+        if let Some(state) = self.find_client(window).cloned() {
+            if !state.is_floating || state.is_fullscreen {
+                return Ok(());
+            }
+
+            let x = if has(8) { data[1] as i16 } else { state.x };
+            let y = if has(9) { data[2] as i16 } else { state.y };
+            let width = if has(10) { data[3] } else { state.width as u32 };
+            let height = if has(11) {
+                data[4]
+            } else {
+                state.height as u32
+            };
+
+            return self.move_resize_client(window, x, y, width, height);
+        }
+
+        let mut aux = ConfigureWindowAux::new();
+        if has(8) {
+            aux = aux.x(data[1] as i32);
+        }
+        if has(9) {
+            aux = aux.y(data[2] as i32);
+        }
+        if has(10) {
+            aux = aux.width(data[3]);
+        }
+        if has(11) {
+            aux = aux.height(data[4]);
+        }
+
+        self.conn.configure_window(window, &aux)?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn set_root_cardinals(&self, property: Atom, value: &[u32]) -> Result<()> {
+        self.conn.change_property32(
+            PropMode::REPLACE,
+            self.root,
+            property,
+            AtomEnum::CARDINAL,
+            value,
+        )?;
+        Ok(())
+    }
+
+    fn set_root_windows(&self, property: Atom, value: &[Window]) -> Result<()> {
+        self.conn.change_property32(
+            PropMode::REPLACE,
+            self.root,
+            property,
+            AtomEnum::WINDOW,
+            value,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,424 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+
+use crate::keybindings::Direction;
+use crate::layout::Rect;
+use crate::wm::WindowManager;
+
+/// The `[floating]` section of the config
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FloatingConfig {
+    /// Distance in pixels at which moved windows snap to edges, 0 turns it off
+    pub snap_distance: u16,
+    /// Dragging a window against a monitor edge offers its halves and quarters
+    pub edge_tiling: bool,
+    /// Pixels a keyboard move or resize goes at a time
+    pub move_step: u16,
+    /// Where new floating windows go
+    pub placement: Placement,
+}
+
+/// Placement policy for new floating windows
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Placement {
+    /// Centered in the monitor's work area
+    Center,
+    /// Centered over the parent of a transient, else like `Center`
+    Parent,
+    /// Centered under the pointer
+    Pointer,
+    /// Each one a step down and right of the last
+    Cascade,
+    /// Where it covers the fewest other floating windows
+    Smart,
+}
+
+/// Offset between cascaded windows
+const CASCADE_STEP: i32 = 32;
+
+impl Default for FloatingConfig {
+    fn default() -> Self {
+        Self {
+            snap_distance: 10,
+            edge_tiling: true,
+            move_step: 20,
+            placement: Placement::Parent,
+        }
+    }
+}
+
+impl WindowManager {
+    /// Move and resize a floating client, keeping it within its size hints
+    pub fn move_resize_client(
+        &mut self,
+        window: Window,
+        x: i16,
+        y: i16,
+        width: u32,
+        height: u32,
+    ) -> Result<()> {
+        let Some(state) = self.monitors.client_mut(window) else {
+            return Ok(());
+        };
+
+        let (width, height) = state.size_hints.constrain(width, height);
+
+        state.x = x;
+        state.y = y;
+        state.width = width as u16;
+        state.height = height as u16;
+
+        if state.is_floating && !state.is_fullscreen {
+            state.floating_geometry = Some(Rect {
+                x,
+                y,
+                width: width as u16,
+                height: height as u16,
+            });
+        }
+
+        self.conn.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(x as i32)
+                .y(y as i32)
+                .width(width)
+                .height(height),
+        )?;
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Move a client between the tiled and the floating layer, floating clients
+    /// get back the geometry they had the last time they floated
+    pub fn toggle_floating(&mut self, window: Window) -> Result<()> {
+        let Some(state) = self.monitors.client(window) else {
+            return Ok(());
+        };
+
+        let is_floating = !state.is_floating;
+        let geometry = state.floating_geometry.unwrap_or(Rect {
+            x: state.x,
+            y: state.y,
+            width: state.width,
+            height: state.height,
+        });
+
+        if !self.set_floating(window, is_floating)? {
+            return Ok(());
+        }
+
+        if is_floating {
+            self.move_resize_client(
+                window,
+                geometry.x,
+                geometry.y,
+                geometry.width as u32,
+                geometry.height as u32,
+            )?;
+        }
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Put a client in or out of the floating layer where it stands, returns
+    /// whether anything changed
+    pub fn set_floating(&mut self, window: Window, floating: bool) -> Result<bool> {
+        let Some(state) = self.monitors.client_mut(window) else {
+            return Ok(false);
+        };
+
+        if state.is_fullscreen || state.is_floating == floating {
+            return Ok(false);
+        }
+
+        state.is_floating = floating;
+
+        println!(
+            "Client {} is now {}",
+            window,
+            if floating { "floating" } else { "tiled" }
+        );
+
+        self.layout()?;
+        self.raise_client(window)?;
+
+        if let Some((monitor_id, workspace_id)) = self.locate_client(window) {
+            self.emit(
+                "client",
+                json!({
+                    "change": "floating",
+                    "window": window,
+                    "monitor": monitor_id,
+                    "workspace": workspace_id,
+                    "floating": floating,
+                }),
+            );
+        }
+
+        Ok(true)
+    }
+
+    /// Nudge a floating client one step, snapping to edges it runs into
+    pub fn move_floating(&mut self, window: Window, direction: Direction) -> Result<()> {
+        let Some(geometry) = self.floating_geometry(window) else {
+            return Ok(());
+        };
+
+        let step = self.config.floating.move_step as i32;
+        let (dx, dy) = direction.delta();
+
+        let x = (geometry.x as i32 + dx * step) as i16;
+        let y = (geometry.y as i32 + dy * step) as i16;
+        let (snapped_x, snapped_y) =
+            self.snap_position(window, x, y, geometry.width, geometry.height);
+
+        // a snap may shorten a step but never pull the window back
+        let x = if (snapped_x as i32 - geometry.x as i32) * dx > 0 {
+            snapped_x
+        } else {
+            x
+        };
+        let y = if (snapped_y as i32 - geometry.y as i32) * dy > 0 {
+            snapped_y
+        } else {
+            y
+        };
+
+        self.move_resize_client(window, x, y, geometry.width as u32, geometry.height as u32)
+    }
+
+    /// Grow a floating client one step to the right or down, or shrink it left or up
+    pub fn resize_floating(&mut self, window: Window, direction: Direction) -> Result<()> {
+        let Some(geometry) = self.floating_geometry(window) else {
+            return Ok(());
+        };
+
+        let step = self.config.floating.move_step as i32;
+        let (dx, dy) = direction.delta();
+
+        let width = (geometry.width as i32 + dx * step).max(1) as u32;
+        let height = (geometry.height as i32 + dy * step).max(1) as u32;
+        let (snapped_width, snapped_height) =
+            self.snap_size(window, geometry.x, geometry.y, width, height);
+
+        let width = if (snapped_width as i32 - geometry.width as i32) * dx > 0 {
+            snapped_width
+        } else {
+            width
+        };
+        let height = if (snapped_height as i32 - geometry.height as i32) * dy > 0 {
+            snapped_height
+        } else {
+            height
+        };
+
+        self.move_resize_client(window, geometry.x, geometry.y, width, height)
+    }
+
+    /// Center a floating client in the work area of its monitor
+    pub fn center_floating(&mut self, window: Window) -> Result<()> {
+        let Some(geometry) = self.floating_geometry(window) else {
+            return Ok(());
+        };
+
+        let Some((monitor_id, _)) = self.locate_client(window) else {
+            return Ok(());
+        };
+        let area = self.monitors.monitors[monitor_id].workarea;
+
+        let outer_width = geometry.width as i32 + self.border_width as i32 * 2;
+        let outer_height = geometry.height as i32 + self.border_width as i32 * 2;
+
+        let x = area.x as i32 + (area.width as i32 - outer_width) / 2;
+        let y = area.y as i32 + (area.height as i32 - outer_height) / 2;
+
+        self.move_resize_client(
+            window,
+            x as i16,
+            y as i16,
+            geometry.width as u32,
+            geometry.height as u32,
+        )
+    }
+
+    /// Current geometry of a client the keyboard can move, floating and not fullscreen
+    fn floating_geometry(&self, window: Window) -> Option<Rect> {
+        self.find_client(window)
+            .filter(|state| state.is_floating && !state.is_fullscreen)
+            .map(|state| Rect {
+                x: state.x,
+                y: state.y,
+                width: state.width,
+                height: state.height,
+            })
+    }
+
+    /// Give a new floating client its requested size at the spot the configured
+    /// placement picks, kept inside the monitor's work area
+    pub fn place_floating(&mut self, window: Window, monitor_id: usize) -> Result<()> {
+        let geometry = self.conn.get_geometry(window)?.reply()?;
+
+        let Some(state) = self.find_client(window) else {
+            return Ok(());
+        };
+
+        let (width, height) = state
+            .size_hints
+            .constrain(geometry.width as u32, geometry.height as u32);
+
+        let area = self.monitors.monitors[monitor_id].workarea;
+        let outer = Rect {
+            x: 0,
+            y: 0,
+            width: (width + self.border_width * 2) as u16,
+            height: (height + self.border_width * 2) as u16,
+        };
+
+        let parent = state
+            .transient_for
+            .and_then(|parent| self.find_client(parent))
+            .map(|parent| Rect {
+                x: parent.x,
+                y: parent.y,
+                width: parent.width + self.border_width as u16 * 2,
+                height: parent.height + self.border_width as u16 * 2,
+            });
+
+        let (x, y) = match self.config.floating.placement {
+            Placement::Center => center_in(outer, area),
+            Placement::Parent => center_in(outer, parent.unwrap_or(area)),
+            Placement::Pointer => {
+                let pointer = self.conn.query_pointer(self.root)?.reply()?;
+                let inside = self.monitors.monitor_at(pointer.root_x, pointer.root_y) == monitor_id;
+
+                if inside {
+                    (
+                        pointer.root_x as i32 - outer.width as i32 / 2,
+                        pointer.root_y as i32 - outer.height as i32 / 2,
+                    )
+                } else {
+                    center_in(outer, area)
+                }
+            }
+            Placement::Cascade => {
+                let count = self.floating_neighbours(window).len() as i32;
+                let free_x = (area.width as i32 - outer.width as i32).max(1);
+                let free_y = (area.height as i32 - outer.height as i32).max(1);
+
+                (
+                    area.x as i32 + (count + 1) * CASCADE_STEP % free_x,
+                    area.y as i32 + (count + 1) * CASCADE_STEP % free_y,
+                )
+            }
+            Placement::Smart => smart_position(outer, area, &self.floating_neighbours(window)),
+        };
+
+        // keep it on the monitor, top left corner first if it is too big
+        let x = x
+            .min(area.x as i32 + area.width as i32 - outer.width as i32)
+            .max(area.x as i32);
+        let y = y
+            .min(area.y as i32 + area.height as i32 - outer.height as i32)
+            .max(area.y as i32);
+
+        self.move_resize_client(window, x as i16, y as i16, width, height)
+    }
+
+    /// Outer geometry of the other floating clients on the workspace of `window`
+    fn floating_neighbours(&self, window: Window) -> Vec<Rect> {
+        let Some((monitor_id, workspace_id)) = self.locate_client(window) else {
+            return Vec::new();
+        };
+
+        let Some(workspace) = self.monitors.monitors[monitor_id]
+            .workspaces
+            .get(workspace_id)
+        else {
+            return Vec::new();
+        };
+
+        let border = self.border_width as u16 * 2;
+
+        workspace
+            .clients
+            .iter()
+            .filter(|&(&other, state)| other != window && state.is_floating && !state.is_fullscreen)
+            .map(|(_, state)| Rect {
+                x: state.x,
+                y: state.y,
+                width: state.width + border,
+                height: state.height + border,
+            })
+            .collect()
+    }
+}
+
+/// Top left corner that centers `outer` over `anchor`
+fn center_in(outer: Rect, anchor: Rect) -> (i32, i32) {
+    (
+        anchor.x as i32 + (anchor.width as i32 - outer.width as i32) / 2,
+        anchor.y as i32 + (anchor.height as i32 - outer.height as i32) / 2,
+    )
+}
+
+fn overlap(a: Rect, b: Rect) -> i64 {
+    let width = (a.x as i64 + a.width as i64).min(b.x as i64 + b.width as i64)
+        - (a.x as i64).max(b.x as i64);
+    let height = (a.y as i64 + a.height as i64).min(b.y as i64 + b.height as i64)
+        - (a.y as i64).max(b.y as i64);
+
+    width.max(0) * height.max(0)
+}
+
+/// The spot in `area` where `outer` covers the least of `others`, trying the area's
+/// corner and every position flush against another window, top left first on ties
+fn smart_position(outer: Rect, area: Rect, others: &[Rect]) -> (i32, i32) {
+    let (width, height) = (outer.width as i32, outer.height as i32);
+    let right = area.x as i32 + area.width as i32 - width;
+    let bottom = area.y as i32 + area.height as i32 - height;
+
+    let mut xs = vec![area.x as i32, right];
+    let mut ys = vec![area.y as i32, bottom];
+    for other in others {
+        xs.extend([other.x as i32 + other.width as i32, other.x as i32 - width]);
+        ys.extend([
+            other.y as i32 + other.height as i32,
+            other.y as i32 - height,
+        ]);
+    }
+
+    xs.retain(|&x| x >= area.x as i32 && x <= right);
+    ys.retain(|&y| y >= area.y as i32 && y <= bottom);
+    xs.sort_unstable();
+    ys.sort_unstable();
+
+    let mut best = (area.x as i32, area.y as i32);
+    let mut best_overlap = i64::MAX;
+
+    for &y in &ys {
+        for &x in &xs {
+            let candidate = Rect {
+                x: x as i16,
+                y: y as i16,
+                width: outer.width,
+                height: outer.height,
+            };
+            let covered: i64 = others.iter().map(|&other| overlap(candidate, other)).sum();
+
+            if covered < best_overlap {
+                best = (x, y);
+                best_overlap = covered;
+            }
+        }
+    }
+
+    best
+}
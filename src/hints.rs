@@ -0,0 +1,360 @@
+use anyhow::Result;
+use serde_json::json;
+use x11rb::connection::Connection;
+use x11rb::properties::{WmHints, WmSizeHints};
+use x11rb::protocol::xproto::*;
+use x11rb::CURRENT_TIME;
+
+use crate::wm::WindowManager;
+
+/// The parts of `WM_NORMAL_HINTS` dxwm acts on, zero meaning unset
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SizeHints {
+    pub base: (u32, u32),
+    pub min: (u32, u32),
+    pub max: (u32, u32),
+    pub increment: (u32, u32),
+    /// Largest height / width ratio, the inverse of the minimum aspect
+    pub min_aspect: f32,
+    /// Largest width / height ratio, the maximum aspect itself
+    pub max_aspect: f32,
+}
+
+impl SizeHints {
+    fn from_wm(hints: WmSizeHints) -> Self {
+        let size = |value: Option<(i32, i32)>| {
+            value.map_or((0, 0), |(w, h)| (w.max(0) as u32, h.max(0) as u32))
+        };
+
+        // ICCCM 4.1.2.3: base and min size stand in for each other
+        let base = size(hints.base_size.or(hints.min_size));
+        let min = size(hints.min_size.or(hints.base_size));
+
+        let (min_aspect, max_aspect) = match hints.aspect {
+            Some((min, max)) if min.numerator > 0 && max.denominator > 0 => (
+                min.denominator as f32 / min.numerator as f32,
+                max.numerator as f32 / max.denominator as f32,
+            ),
+            _ => (0.0, 0.0),
+        };
+
+        Self {
+            base,
+            min,
+            max: size(hints.max_size),
+            increment: size(hints.size_increment),
+            min_aspect,
+            max_aspect,
+        }
+    }
+
+    /// Windows that cannot be resized are better off floating
+    pub fn is_fixed(&self) -> bool {
+        self.max.0 > 0 && self.max.1 > 0 && self.max == self.min
+    }
+
+    /// Closest size to the one asked for that satisfies every hint
+    pub fn constrain(&self, width: u32, height: u32) -> (u32, u32) {
+        let (base_w, base_h) = self.base;
+        let base_is_min = self.base == self.min;
+
+        let (mut w, mut h) = (width.max(1), height.max(1));
+
+        // the base size is only left out of the aspect ratio when it is not the minimum
+        if !base_is_min {
+            w = w.saturating_sub(base_w);
+            h = h.saturating_sub(base_h);
+        }
+
+        if self.min_aspect > 0.0 && self.max_aspect > 0.0 && w > 0 && h > 0 {
+            if self.max_aspect < w as f32 / h as f32 {
+                w = (h as f32 * self.max_aspect + 0.5) as u32;
+            } else if self.min_aspect < h as f32 / w as f32 {
+                h = (w as f32 * self.min_aspect + 0.5) as u32;
+            }
+        }
+
+        if base_is_min {
+            w = w.saturating_sub(base_w);
+            h = h.saturating_sub(base_h);
+        }
+
+        (w, h) = self.round_to_increments(w, h);
+
+        w = (w + base_w).max(self.min.0);
+        h = (h + base_h).max(self.min.1);
+
+        if self.max.0 > 0 {
+            w = w.min(self.max.0);
+        }
+        if self.max.1 > 0 {
+            h = h.min(self.max.1);
+        }
+
+        (w.max(1), h.max(1))
+    }
+
+    /// Shrink a tile so it holds a whole number of resize increments
+    pub fn fit_increments(&self, width: u32, height: u32) -> (u32, u32) {
+        let (base_w, base_h) = self.base;
+
+        if width <= base_w || height <= base_h {
+            return (width, height);
+        }
+
+        let (w, h) = self.round_to_increments(width - base_w, height - base_h);
+        (w + base_w, h + base_h)
+    }
+
+    fn round_to_increments(&self, width: u32, height: u32) -> (u32, u32) {
+        let (inc_w, inc_h) = self.increment;
+
+        let width = if inc_w > 1 {
+            width - width % inc_w
+        } else {
+            width
+        };
+        let height = if inc_h > 1 {
+            height - height % inc_h
+        } else {
+            height
+        };
+
+        (width, height)
+    }
+}
+
+impl WindowManager {
+    /// Read `WM_NORMAL_HINTS`, missing or malformed hints mean no constraints
+    pub fn read_size_hints(&self, window: Window) -> Result<SizeHints> {
+        let hints = WmSizeHints::get_normal_hints(&self.conn, window)?
+            .reply()
+            .ok()
+            .flatten();
+
+        Ok(hints.map(SizeHints::from_wm).unwrap_or_default())
+    }
+
+    /// Read WM_HINTS and WM_PROTOCOLS for the input model and urgency
+    pub fn update_wm_hints(&mut self, window: Window) -> Result<()> {
+        let hints = WmHints::get(&self.conn, window)?.reply().ok().flatten();
+        let takes_focus = self.client_supports_protocol(window, self.atoms.wm_take_focus)?;
+
+        let urgent = hints.is_some_and(|h| h.urgent);
+        let is_focused = self.focused_client() == Some(window);
+
+        let Some(state) = self.monitors.client_mut(window) else {
+            return Ok(());
+        };
+
+        state.accepts_input = hints.and_then(|h| h.input).unwrap_or(true);
+        state.takes_focus = takes_focus;
+
+        // the focused client already has the user's attention
+        let urgent = urgent && !is_focused;
+        if state.is_urgent != urgent {
+            self.set_urgent(window, urgent)?;
+        }
+
+        Ok(())
+    }
+
+    /// Mark or clear a client as demanding attention
+    pub fn set_urgent(&mut self, window: Window, urgent: bool) -> Result<()> {
+        let Some((monitor_id, workspace_id)) = self.locate_client(window) else {
+            return Ok(());
+        };
+
+        if let Some(state) = self.monitors.client_mut(window) {
+            state.is_urgent = urgent;
+        }
+
+        self.update_net_wm_state(window)?;
+
+        if self.focused_client() != Some(window) {
+            let color = if urgent {
+                self.config.appearance.border_urgent
+            } else {
+                self.border_unfocused_color
+            };
+
+            self.conn.change_window_attributes(
+                window,
+                &ChangeWindowAttributesAux::new().border_pixel(color),
+            )?;
+        }
+
+        if urgent {
+            let title = self.window_title(window)?;
+            self.draw_alert(format!("[URGENT] {} on workspace {}", title, workspace_id))?;
+        }
+
+        self.emit(
+            "client",
+            json!({
+                "change": "urgent",
+                "window": window,
+                "monitor": monitor_id,
+                "workspace": workspace_id,
+                "urgent": urgent,
+            }),
+        );
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Hand the input focus to a client according to its ICCCM input model
+    pub fn give_input_focus(&mut self, window: Window) -> Result<()> {
+        let (accepts_input, takes_focus, is_urgent) = self
+            .find_client(window)
+            .map_or((true, false, false), |state| {
+                (state.accepts_input, state.takes_focus, state.is_urgent)
+            });
+
+        // passive and locally active clients get the focus set, no-input ones never do
+        if accepts_input {
+            self.conn
+                .set_input_focus(InputFocus::PARENT, window, CURRENT_TIME)?;
+        }
+
+        // locally and globally active clients are told to take it
+        if takes_focus {
+            self.send_protocol(window, self.atoms.wm_take_focus)?;
+        }
+
+        if is_urgent {
+            self.set_urgent(window, false)?;
+        }
+
+        Ok(())
+    }
+
+    /// `_NET_WM_NAME`, falling back to `WM_NAME`
+    pub fn window_title(&self, window: Window) -> Result<String> {
+        for (property, type_) in [
+            (self.atoms.net_wm_name, self.atoms.utf8_string),
+            (AtomEnum::WM_NAME.into(), AtomEnum::STRING.into()),
+        ] {
+            let reply = self
+                .conn
+                .get_property(false, window, property, type_, 0, 256)?
+                .reply()?;
+
+            if !reply.value.is_empty() {
+                return Ok(String::from_utf8_lossy(&reply.value).to_string());
+            }
+        }
+
+        Ok(format!("window {}", window))
+    }
+
+    /// Pick up new size hints and re-apply them to the client's geometry
+    pub fn update_size_hints(&mut self, window: Window) -> Result<()> {
+        let hints = self.read_size_hints(window)?;
+
+        let Some(state) = self.monitors.client_mut(window) else {
+            return Ok(());
+        };
+
+        if state.size_hints == hints {
+            return Ok(());
+        }
+        state.size_hints = hints;
+
+        if state.is_floating && !state.is_fullscreen {
+            let (x, y, width, height) = (state.x, state.y, state.width, state.height);
+            self.move_resize_client(window, x, y, width as u32, height as u32)
+        } else {
+            self.layout_all_monitors()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x11rb::properties::AspectRatio;
+
+    #[test]
+    fn base_and_min_stand_in_for_each_other() {
+        let mut hints = WmSizeHints::new();
+        hints.min_size = Some((100, 50));
+        let only_min = SizeHints::from_wm(hints);
+        assert_eq!(only_min.base, (100, 50));
+        assert_eq!(only_min.min, (100, 50));
+
+        let mut hints = WmSizeHints::new();
+        hints.base_size = Some((30, 40));
+        let only_base = SizeHints::from_wm(hints);
+        assert_eq!(only_base.base, (30, 40));
+        assert_eq!(only_base.min, (30, 40));
+    }
+
+    #[test]
+    fn constrain_without_hints_keeps_the_size() {
+        assert_eq!(SizeHints::default().constrain(640, 480), (640, 480));
+        assert_eq!(SizeHints::default().constrain(0, 0), (1, 1));
+    }
+
+    #[test]
+    fn constrain_rounds_to_increments_above_the_base() {
+        let hints = SizeHints {
+            base: (10, 20),
+            min: (10, 20),
+            increment: (7, 13),
+            ..SizeHints::default()
+        };
+
+        assert_eq!(hints.constrain(100, 100), (94, 98));
+        assert_eq!(hints.constrain(5, 5), (10, 20));
+    }
+
+    #[test]
+    fn constrain_clamps_to_the_aspect_range() {
+        // width / height between 1/2 and 2
+        let mut wm = WmSizeHints::new();
+        wm.aspect = Some((AspectRatio::new(1, 2), AspectRatio::new(2, 1)));
+        let hints = SizeHints::from_wm(wm);
+
+        assert_eq!(hints.min_aspect, 2.0);
+        assert_eq!(hints.max_aspect, 2.0);
+        assert_eq!(hints.constrain(400, 100), (200, 100));
+        assert_eq!(hints.constrain(100, 400), (100, 200));
+        assert_eq!(hints.constrain(150, 100), (150, 100));
+    }
+
+    #[test]
+    fn constrain_clamps_to_min_and_max() {
+        let hints = SizeHints {
+            base: (50, 60),
+            min: (50, 60),
+            max: (300, 200),
+            ..SizeHints::default()
+        };
+
+        assert_eq!(hints.constrain(500, 500), (300, 200));
+        assert_eq!(hints.constrain(10, 10), (50, 60));
+        assert!(!hints.is_fixed());
+
+        let fixed = SizeHints {
+            min: (300, 200),
+            ..hints
+        };
+        assert!(fixed.is_fixed());
+    }
+
+    #[test]
+    fn fit_increments_shrinks_to_whole_cells() {
+        let hints = SizeHints {
+            base: (4, 4),
+            min: (4, 4),
+            increment: (10, 20),
+            ..SizeHints::default()
+        };
+
+        assert_eq!(hints.fit_increments(99, 99), (94, 84));
+        assert_eq!(hints.fit_increments(3, 105), (3, 105));
+        assert_eq!(SizeHints::default().fit_increments(105, 105), (105, 105));
+    }
+}
@@ -0,0 +1,544 @@
+use std::env;
+use std::fs;
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::wrapper::ConnectionExt as _;
+
+use crate::keybindings::KeyAction;
+use crate::keysyms::parse_key_chord;
+use crate::wm::WindowManager;
+
+/// Environment variable advertising the socket to spawned processes
+pub const SOCKET_ENV: &str = "DXWM_SOCKET";
+
+/// Drop a client whose pending output grows past this many bytes
+const MAX_OUTBOX: usize = 1 << 20;
+
+/// Drop a client whose unfinished request line grows past this many bytes
+const MAX_INBOX: usize = 1 << 16;
+
+/// Events a client can subscribe to
+pub const EVENTS: &[&str] = &["workspace", "focus", "layout", "mode", "monitor", "client"];
+
+/// Return the socket path for the current `$DISPLAY`
+pub fn socket_path() -> PathBuf {
+    let display = env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string());
+    let display: String = display
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join(format!("dxwm{}.sock", display)),
+        _ => {
+            let user = env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+            env::temp_dir().join(format!("dxwm-{}{}.sock", user, display))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// A command as received from a JSON client
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonRequest {
+    command: String,
+    #[serde(default)]
+    args: Vec<Value>,
+}
+
+struct IpcClient {
+    id: u64,
+    stream: UnixStream,
+    inbox: Vec<u8>,
+    outbox: Vec<u8>,
+    hung_up: bool,
+    dead: bool,
+    /// Subscribed events, empty meaning all of them
+    subscriptions: Option<Vec<String>>,
+}
+
+pub struct IpcRequest {
+    pub client: u64,
+    pub format: Format,
+    pub line: String,
+}
+
+pub struct IpcServer {
+    pub path: PathBuf,
+    listener: UnixListener,
+    clients: Vec<IpcClient>,
+    next_id: u64,
+    last_focus: Option<Window>,
+}
+
+impl IpcServer {
+    /// Bind the socket, replacing a stale one left by a crashed instance
+    pub fn bind() -> Result<Self> {
+        let path = socket_path();
+
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                bail!("another dxwm instance is listening on {}", path.display());
+            }
+            fs::remove_file(&path).ok();
+        }
+
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("Failed to bind {}", path.display()))?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            path,
+            listener,
+            clients: Vec::new(),
+            next_id: 0,
+            last_focus: None,
+        })
+    }
+
+    /// Accept new connections and collect every complete line received
+    pub fn poll(&mut self) -> Vec<IpcRequest> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if stream.set_nonblocking(true).is_err() {
+                        continue;
+                    }
+                    self.clients.push(IpcClient {
+                        id: self.next_id,
+                        stream,
+                        inbox: Vec::new(),
+                        outbox: Vec::new(),
+                        hung_up: false,
+                        dead: false,
+                        subscriptions: None,
+                    });
+                    self.next_id += 1;
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    eprintln!("Error accepting IPC connection: {}", err);
+                    break;
+                }
+            }
+        }
+
+        let mut requests = Vec::new();
+        let mut buf = [0u8; 4096];
+
+        for client in self.clients.iter_mut().filter(|c| !c.hung_up && !c.dead) {
+            loop {
+                match client.stream.read(&mut buf) {
+                    Ok(0) => {
+                        client.hung_up = true;
+                        break;
+                    }
+                    Ok(n) => {
+                        client.inbox.extend_from_slice(&buf[..n]);
+
+                        // the rest waits for the next poll, after these lines ran
+                        if client.inbox.len() > MAX_INBOX {
+                            break;
+                        }
+                    }
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                    Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                    Err(_) => {
+                        client.dead = true;
+                        break;
+                    }
+                }
+            }
+
+            while let Some(end) = client.inbox.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = client.inbox.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line).trim().to_string();
+
+                if line.is_empty() {
+                    continue;
+                }
+
+                let format = if line.starts_with('{') {
+                    Format::Json
+                } else {
+                    Format::Text
+                };
+
+                requests.push(IpcRequest {
+                    client: client.id,
+                    format,
+                    line,
+                });
+            }
+
+            // a line that never ends would grow the buffer without limit
+            if client.inbox.len() > MAX_INBOX {
+                client.inbox.clear();
+                client.dead = true;
+            }
+        }
+
+        requests
+    }
+
+    /// Queue a line of output for a client
+    pub fn send(&mut self, client: u64, line: &str) {
+        if let Some(client) = self.clients.iter_mut().find(|c| c.id == client) {
+            client.outbox.extend_from_slice(line.as_bytes());
+            client.outbox.push(b'\n');
+        }
+    }
+
+    /// Start streaming events to a client
+    pub fn subscribe(&mut self, client: u64, events: Vec<String>) {
+        if let Some(client) = self.clients.iter_mut().find(|c| c.id == client) {
+            client.subscriptions = Some(events);
+        }
+    }
+
+    /// Queue an event line for every client subscribed to it
+    pub fn broadcast(&mut self, event: &str, line: &str) {
+        for client in &mut self.clients {
+            let subscribed = match &client.subscriptions {
+                Some(events) => events.is_empty() || events.iter().any(|e| e == event),
+                None => false,
+            };
+
+            if subscribed && !client.dead {
+                client.outbox.extend_from_slice(line.as_bytes());
+                client.outbox.push(b'\n');
+            }
+        }
+    }
+
+    /// Write pending output and drop closed, dead or stalled clients
+    pub fn flush(&mut self) {
+        for client in &mut self.clients {
+            while !client.outbox.is_empty() {
+                match client.stream.write(&client.outbox) {
+                    Ok(0) => {
+                        client.dead = true;
+                        break;
+                    }
+                    Ok(n) => {
+                        client.outbox.drain(..n);
+                    }
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                    Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                    Err(_) => {
+                        client.dead = true;
+                        break;
+                    }
+                }
+            }
+
+            if client.outbox.len() > MAX_OUTBOX {
+                client.dead = true;
+            }
+        }
+
+        // a client that only closed its write side still gets its replies,
+        // subscribers stay until writing to them fails
+        self.clients.retain(|c| {
+            !c.dead && (!c.hung_up || !c.outbox.is_empty() || c.subscriptions.is_some())
+        });
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+/// Turn a request line into a text command, JSON args are appended in order
+fn command_line(request: &IpcRequest) -> Result<String, String> {
+    match request.format {
+        Format::Text => Ok(request.line.clone()),
+        Format::Json => {
+            let json: JsonRequest =
+                serde_json::from_str(&request.line).map_err(|err| err.to_string())?;
+
+            let mut line = json.command;
+            for arg in json.args {
+                line.push(' ');
+                match arg {
+                    Value::String(s) => line.push_str(&s),
+                    other => line.push_str(&other.to_string()),
+                }
+            }
+            Ok(line)
+        }
+    }
+}
+
+/// Render a command result in the format the client used
+fn render_reply(format: Format, result: Result<Option<Value>, String>) -> String {
+    match (format, result) {
+        (Format::Json, Ok(None)) => json!({ "ok": true }).to_string(),
+        (Format::Json, Ok(Some(data))) => json!({ "ok": true, "data": data }).to_string(),
+        (Format::Json, Err(err)) => json!({ "ok": false, "error": err }).to_string(),
+        (Format::Text, Ok(None)) => "ok".to_string(),
+        (Format::Text, Ok(Some(data))) => render_text(&data),
+        (Format::Text, Err(err)) => format!("error: {}", err),
+    }
+}
+
+/// Lists of flat objects become `key=value` lines, anything else pretty JSON
+fn render_text(data: &Value) -> String {
+    let render_field = |(key, value): (&String, &Value)| match value {
+        Value::String(s) => format!("{}={}", key, s),
+        other => format!("{}={}", key, other),
+    };
+
+    match data {
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(is_flat_object) => items
+            .iter()
+            .filter_map(Value::as_object)
+            .map(|object| {
+                object
+                    .iter()
+                    .map(render_field)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => serde_json::to_string_pretty(other).unwrap_or_else(|err| err.to_string()),
+    }
+}
+
+fn is_flat_object(value: &Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|object| object.values().all(|v| !v.is_object() && !v.is_array()))
+}
+
+impl WindowManager {
+    /// Start the IPC server and advertise it to children and X clients
+    pub fn setup_ipc(&mut self) -> Result<()> {
+        let server = IpcServer::bind()?;
+        let path = server.path.to_string_lossy().to_string();
+
+        // SAFETY: called during startup, before any other thread is spawned
+        unsafe { env::set_var(SOCKET_ENV, &path) };
+
+        self.conn.change_property8(
+            PropMode::REPLACE,
+            self.root,
+            self.atoms.dxwm_socket,
+            self.atoms.utf8_string,
+            path.as_bytes(),
+        )?;
+        self.conn.flush()?;
+
+        println!("IPC listening on {}", path);
+        self.ipc = Some(server);
+        Ok(())
+    }
+
+    /// Service pending IPC requests without blocking, returns whether any were handled
+    pub fn handle_ipc(&mut self) -> bool {
+        let Some(requests) = self.ipc.as_mut().map(IpcServer::poll) else {
+            return false;
+        };
+        let handled = !requests.is_empty();
+
+        for request in requests {
+            let result = command_line(&request).and_then(|line| {
+                match line.split_once(char::is_whitespace).unwrap_or((&line, "")) {
+                    ("subscribe", events) => self.subscribe(request.client, events),
+                    _ => self.run_ipc_command(&line),
+                }
+            });
+            let reply = render_reply(request.format, result);
+
+            if let Some(ipc) = self.ipc.as_mut() {
+                ipc.send(request.client, &reply);
+            }
+        }
+
+        // focus changes from many places, so it is reported by comparison
+        let focus = self.focused_client();
+        if self.ipc.as_ref().is_some_and(|ipc| ipc.last_focus != focus) {
+            self.emit("focus", json!({ "window": focus }));
+            if let Some(ipc) = self.ipc.as_mut() {
+                ipc.last_focus = focus;
+            }
+        }
+
+        if let Some(ipc) = self.ipc.as_mut() {
+            ipc.flush();
+        }
+
+        handled
+    }
+
+    /// Register a client for `subscribe [event...]`
+    fn subscribe(&mut self, client: u64, events: &str) -> Result<Option<Value>, String> {
+        let events: Vec<String> = events.split_whitespace().map(str::to_string).collect();
+
+        if let Some(unknown) = events.iter().find(|e| !EVENTS.contains(&e.as_str())) {
+            return Err(format!(
+                "unknown event `{}`, expected one of: {}",
+                unknown,
+                EVENTS.join(", ")
+            ));
+        }
+
+        if let Some(ipc) = self.ipc.as_mut() {
+            ipc.subscribe(client, events);
+        }
+        Ok(None)
+    }
+
+    /// Send an event as a line of JSON to the subscribed IPC clients
+    pub fn emit(&mut self, event: &str, data: Value) {
+        let Some(ipc) = self.ipc.as_mut() else {
+            return;
+        };
+
+        let mut payload = json!({ "event": event });
+        if let (Some(payload), Value::Object(data)) = (payload.as_object_mut(), data) {
+            payload.extend(data);
+        }
+
+        ipc.broadcast(event, &payload.to_string());
+    }
+
+    /// Run a single text command, e.g. `workspace 3` or `bind Super+x close`
+    pub fn run_ipc_command(&mut self, line: &str) -> Result<Option<Value>, String> {
+        let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+        match name {
+            "query" => self.query(rest.trim()).map(Some),
+            "tree" => self.query("tree").map(Some),
+            "bind" => {
+                let (chord, action) = rest
+                    .trim()
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| "usage: bind <keys> <action>".to_string())?;
+
+                let (keysym, modifiers) = parse_key_chord(chord)?;
+                let action = action.parse::<KeyAction>()?;
+
+                self.bind_key(keysym, modifiers, action)
+                    .map_err(|err| err.to_string())?;
+                Ok(None)
+            }
+            _ => {
+                let action = line.parse::<KeyAction>()?;
+                self.execute_action(action).map_err(|err| err.to_string())?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Answer `query clients|workspaces|monitors|tree`
+    fn query(&self, what: &str) -> Result<Value, String> {
+        let monitors = &self.monitors.monitors;
+
+        let data = match what {
+            "tree" => return self.state_tree(),
+            "clients" => monitors
+                .iter()
+                .flat_map(|monitor| {
+                    monitor.workspaces.workspaces.iter().flat_map(move |ws| {
+                        ws.clients_order().into_iter().filter_map(move |window| {
+                            let state = ws.clients.get(&window)?;
+                            Some(json!({
+                                "window": window,
+                                "monitor": monitor.id,
+                                "workspace": ws.id,
+                                "focused": ws.focused_client == Some(window),
+                                "fullscreen": state.is_fullscreen,
+                                "floating": state.is_floating,
+                                "urgent": state.is_urgent,
+                                "x": state.x,
+                                "y": state.y,
+                                "width": state.width,
+                                "height": state.height,
+                            }))
+                        })
+                    })
+                })
+                .collect(),
+            "workspaces" => monitors
+                .iter()
+                .flat_map(|monitor| {
+                    monitor.workspaces.workspaces.iter().map(move |ws| {
+                        json!({
+                            "monitor": monitor.id,
+                            "id": ws.id,
+                            "name": ws.name,
+                            "current": monitor.workspaces.current_workspace == ws.id,
+                            "clients": ws.clients.len(),
+                            "urgent": ws.clients.values().any(|state| state.is_urgent),
+                            "layout": ws.layout_config.current,
+                        })
+                    })
+                })
+                .collect(),
+            "monitors" => monitors
+                .iter()
+                .map(|monitor| {
+                    json!({
+                        "id": monitor.id,
+                        "name": monitor.name,
+                        "focused": self.monitors.current_monitor == monitor.id,
+                        "primary": monitor.primary,
+                        "x": monitor.x,
+                        "y": monitor.y,
+                        "width": monitor.width,
+                        "height": monitor.height,
+                        "workspace": monitor.workspaces.current_workspace,
+                    })
+                })
+                .collect(),
+            _ => return Err(format!("unknown query `{}`", what)),
+        };
+
+        Ok(Value::Array(data))
+    }
+
+    /// Serialize the whole monitor, workspace and client model
+    fn state_tree(&self) -> Result<Value, String> {
+        let alerts: Vec<Value> = self
+            .alerts
+            .iter()
+            .map(|alert| {
+                json!({
+                    "window": alert.window,
+                    "message": alert.message,
+                    "age_ms": alert.created_at.elapsed().as_millis() as u64,
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "monitors": serde_json::to_value(&self.monitors).map_err(|err| err.to_string())?,
+            "mode": self.keybindings.current_mode,
+            "alerts": alerts,
+        }))
+    }
+}
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
+use std::str::FromStr;
 use x11rb::protocol::xproto::{Keycode, ModMask};
 
+use crate::layouts::layout_by_name;
+
 #[derive(Debug, Clone)]
 pub enum KeyAction {
     Spawn(String),
@@ -9,12 +12,144 @@ pub enum KeyAction {
     CloseWindow,
     FocusNext,
     FocusPrev,
+    SwapNext,
+    SwapPrev,
+    PromoteToMaster,
+    RotateWindows,
+    ToggleFullscreen,
+    ToggleFloating,
+    MoveFloating(Direction),
+    ResizeFloating(Direction),
+    CenterFloating,
+    NextLayout,
+    SetLayout(String),
+    IncreaseMasterRatio,
+    DecreaseMasterRatio,
+    IncreaseNMaster,
+    DecreaseNMaster,
+    IncreaseGap,
+    DecreaseGap,
     Quit,
     SwitchWorkspace(u8),
     MoveToWorkspace(u8),
+    CycleLastWorkspace,
+    FocusNextMonitor,
+    FocusPrevMonitor,
+    FocusMonitor(usize),
+    MoveToNextMonitor,
+    MoveToPrevMonitor,
+    MoveToMonitor(usize),
+    RefreshMonitors,
+    Banish,
+    ClearAlerts,
+    Reload,
     Custom(fn(&mut crate::wm::WindowManager)),
 }
 
+impl FromStr for KeyAction {
+    type Err = String;
+
+    /// Parse an action written as `name [argument]`, e.g. `workspace 3`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, arg) = match s.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (s, ""),
+        };
+
+        let action = match (name, arg) {
+            ("spawn", cmd) if !cmd.is_empty() => KeyAction::Spawn(cmd.to_string()),
+            ("mode", mode) if !mode.is_empty() => KeyAction::EnterMode(mode.to_string()),
+            ("exit-mode", "") => KeyAction::ExitMode,
+            ("close", "") => KeyAction::CloseWindow,
+            ("focus-next", "") => KeyAction::FocusNext,
+            ("focus-prev", "") => KeyAction::FocusPrev,
+            ("swap-next", "") => KeyAction::SwapNext,
+            ("swap-prev", "") => KeyAction::SwapPrev,
+            ("promote", "") => KeyAction::PromoteToMaster,
+            ("rotate", "") => KeyAction::RotateWindows,
+            ("fullscreen", "") => KeyAction::ToggleFullscreen,
+            ("toggle-floating", "") => KeyAction::ToggleFloating,
+            ("move-floating", dir) => KeyAction::MoveFloating(parse_direction(name, dir)?),
+            ("resize-floating", dir) => KeyAction::ResizeFloating(parse_direction(name, dir)?),
+            ("center-floating", "") => KeyAction::CenterFloating,
+            ("next-layout", "") | ("layout", "next") => KeyAction::NextLayout,
+            ("layout", layout) if layout_by_name(layout).is_some() => {
+                KeyAction::SetLayout(layout.to_string())
+            }
+            ("increase-master-ratio", "") => KeyAction::IncreaseMasterRatio,
+            ("decrease-master-ratio", "") => KeyAction::DecreaseMasterRatio,
+            ("increase-nmaster", "") => KeyAction::IncreaseNMaster,
+            ("decrease-nmaster", "") => KeyAction::DecreaseNMaster,
+            ("increase-gap", "") => KeyAction::IncreaseGap,
+            ("decrease-gap", "") => KeyAction::DecreaseGap,
+            ("quit", "") => KeyAction::Quit,
+            ("workspace", id) => KeyAction::SwitchWorkspace(parse_arg(name, id)?),
+            ("move-to-workspace", id) => KeyAction::MoveToWorkspace(parse_arg(name, id)?),
+            ("last-workspace", "") => KeyAction::CycleLastWorkspace,
+            ("focus-monitor", "next") => KeyAction::FocusNextMonitor,
+            ("focus-monitor", "prev") => KeyAction::FocusPrevMonitor,
+            ("focus-monitor", id) => KeyAction::FocusMonitor(parse_arg(name, id)?),
+            ("move-to-monitor", "next") => KeyAction::MoveToNextMonitor,
+            ("move-to-monitor", "prev") => KeyAction::MoveToPrevMonitor,
+            ("move-to-monitor", id) => KeyAction::MoveToMonitor(parse_arg(name, id)?),
+            ("refresh-monitors", "") => KeyAction::RefreshMonitors,
+            ("banish", "") => KeyAction::Banish,
+            ("clear-alerts", "") => KeyAction::ClearAlerts,
+            ("reload", "") => KeyAction::Reload,
+            (_, "") => return Err(format!("unknown action `{}`", s)),
+            _ => return Err(format!("invalid arguments for `{}`: `{}`", name, arg)),
+        };
+
+        Ok(action)
+    }
+}
+
+fn parse_arg<T: FromStr>(name: &str, arg: &str) -> Result<T, String> {
+    arg.parse()
+        .map_err(|_| format!("`{}` expects a number, got `{}`", name, arg))
+}
+
+fn parse_direction(name: &str, arg: &str) -> Result<Direction, String> {
+    arg.parse()
+        .map_err(|_| format!("`{}` expects left, right, up or down, got `{}`", name, arg))
+}
+
+/// Where a keyboard move or resize goes, resizing grows towards right and down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    /// Unit step along x and y
+    pub fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Direction::Left),
+            "right" => Ok(Direction::Right),
+            "up" => Ok(Direction::Up),
+            "down" => Ok(Direction::Down),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct KeyBinding {
     pub keycode: Keycode,
@@ -44,25 +179,21 @@ impl KeyBindingManager {
         }
     }
 
-    /// Create a "binding" in normal mode
+    /// Create a "binding" in normal mode, replacing any binding on the same keys
     pub fn bind_normal(&mut self, keycode: Keycode, modifiers: ModMask, action: KeyAction) {
-        self.normal_bindings.push(KeyBinding {
-            keycode,
-            modifiers,
-            action,
-        });
+        bind(&mut self.normal_bindings, keycode, modifiers, action);
     }
 
-    /// Create "sub-binding"
+    /// Create "sub-binding", an existing submap keeps its bindings
     pub fn add_submap(&mut self, name: String, oneshot: bool) {
-        self.submaps.insert(
-            name.clone(),
-            SubMap {
+        self.submaps
+            .entry(name.clone())
+            .and_modify(|submap| submap.oneshot = oneshot)
+            .or_insert(SubMap {
                 name,
                 bindings: Vec::new(),
                 oneshot,
-            },
-        );
+            });
     }
 
     /// Add a "binding" to a submap
@@ -74,11 +205,7 @@ impl KeyBindingManager {
         action: KeyAction,
     ) {
         if let Some(submap) = self.submaps.get_mut(mode) {
-            submap.bindings.push(KeyBinding {
-                keycode,
-                modifiers,
-                action,
-            });
+            bind(&mut submap.bindings, keycode, modifiers, action);
         }
     }
 
@@ -149,3 +276,12 @@ impl KeyBindingManager {
         self.current_mode.is_some()
     }
 }
+
+fn bind(bindings: &mut Vec<KeyBinding>, keycode: Keycode, modifiers: ModMask, action: KeyAction) {
+    bindings.retain(|b| b.keycode != keycode || b.modifiers != modifiers);
+    bindings.push(KeyBinding {
+        keycode,
+        modifiers,
+        action,
+    });
+}
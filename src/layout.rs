@@ -1,211 +1,143 @@
-use std::collections::HashMap;
-
-use crate::clients::ClientState;
 use crate::config::config::MARGIN;
+use crate::layouts::{layout_by_name, Layout, MasterStack, LAYOUTS};
 use crate::wm::WindowManager;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
-use x11rb::CURRENT_TIME;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum LayoutType {
-    MasterStack,
-    Monocle,
+/// A screen rectangle in root window coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Rect {
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct LayoutConfig {
-    pub current: LayoutType,
+    /// Name of the layout in use, the one workspaces start with in the config
+    #[serde(rename(deserialize = "default"))]
+    pub current: String,
+    /// Layouts `next-layout` cycles through, in order
+    pub layouts: Vec<String>,
     pub master_ratio: f32,
     pub nmaster: usize,
     pub gap_size: i16,
     pub screen_padding: i16,
+    /// Shrink tiles to whole resize increments, e.g. terminal character cells
+    pub respect_increments: bool,
 }
 
 impl Default for LayoutConfig {
     fn default() -> Self {
         Self {
-            current: LayoutType::MasterStack,
+            current: MasterStack::NAME.to_string(),
+            layouts: LAYOUTS
+                .iter()
+                .map(|layout| layout.name().to_string())
+                .collect(),
             master_ratio: 0.5,
             nmaster: 1,
             gap_size: MARGIN as i16,
             screen_padding: MARGIN as i16,
+            respect_increments: false,
         }
     }
 }
 
-#[allow(dead_code)]
-impl WindowManager {
-    pub fn layout(&mut self) -> Result<()> {
-        let non_fullscreen_clients: HashMap<Window, ClientState> = self
-            .clients()
-            .iter()
-            .filter(|(_, state)| !state.is_fullscreen)
-            .map(|(&w, s)| (w, s.clone()))
-            .collect();
-
-        if non_fullscreen_clients.is_empty() {
-            return Ok(());
+impl LayoutConfig {
+    /// Take the values `new` changes from `old`, keeping runtime adjustments to
+    /// the others
+    pub fn apply_changes(&mut self, old: &LayoutConfig, new: &LayoutConfig) {
+        if new.current != old.current {
+            self.current = new.current.clone();
         }
-        let workspace_layout = self
-            .monitors
-            .current_mut()
-            .workspaces
-            .current()
-            .layout_config
-            .clone();
-
-        match workspace_layout.current {
-            LayoutType::MasterStack => self.apply_master_stack_layout()?,
-            LayoutType::Monocle => self.apply_monocle_layout()?,
+        if new.layouts != old.layouts {
+            self.layouts = new.layouts.clone();
+        }
+        if new.master_ratio != old.master_ratio {
+            self.master_ratio = new.master_ratio;
+        }
+        if new.nmaster != old.nmaster {
+            self.nmaster = new.nmaster;
+        }
+        if new.gap_size != old.gap_size {
+            self.gap_size = new.gap_size;
+        }
+        if new.screen_padding != old.screen_padding {
+            self.screen_padding = new.screen_padding;
+        }
+        if new.respect_increments != old.respect_increments {
+            self.respect_increments = new.respect_increments;
         }
-
-        self.restack_alerts()?;
-        self.conn.flush()?;
-        Ok(())
     }
+}
 
-    pub fn apply_master_stack_layout(&mut self) -> Result<()> {
-        let monitor = self.monitors.current().clone();
-
+#[allow(dead_code)]
+impl WindowManager {
+    pub fn layout(&mut self) -> Result<()> {
         self.monitors
             .current_mut()
             .workspaces
             .current_mut()
             .sync_clients();
 
-        let workspace = self.monitors.current().workspaces.current();
-
-        let padding = workspace.layout_config.screen_padding;
-
-        let screen_width = monitor.width as i16 - padding * 2;
-        let screen_height = monitor.height as i16 - padding * 2;
-        let screen_x = monitor.x.saturating_add(padding);
-        let screen_y = monitor.y.saturating_add(padding);
-
-        let gap = workspace.layout_config.gap_size;
-        let nmaster = workspace.layout_config.nmaster;
-
-        let clients: Vec<Window> = workspace
-            .clients_order()
-            .into_iter()
-            .filter(|w| {
-                if let Some(state) = workspace.clients.get(w) {
-                    !state.is_fullscreen
-                } else {
-                    false
-                }
-            })
-            .collect();
-
-        let n_clients = clients.len();
-
-        if n_clients == 0 {
-            return Ok(());
-        }
-
-        if n_clients == 1 {
-            let client = clients[0];
-            self.configure_client(client, screen_x, screen_y, screen_width, screen_height)?;
+        let clients = self.tiled_clients();
+        if clients.is_empty() {
             return Ok(());
         }
 
-        let n_master = nmaster.min(n_clients);
-        let n_stack = n_clients - n_master;
-
-        let master_width = if n_stack > 0 {
-            ((screen_width as f32 * workspace.layout_config.master_ratio) as i16) - gap
-        } else {
-            screen_width
-        };
-
-        let stack_width = if n_stack > 0 {
-            screen_width - master_width - gap
-        } else {
-            0
-        };
-
-        // Master
-        if n_master == 1 {
-            let client = clients[0];
-            self.configure_client(client, screen_x, screen_y, master_width, screen_height)?;
-        } else {
-            let master_height = (screen_height - (gap * (n_master as i16 - 1))) / n_master as i16;
-
-            for (i, &client) in clients.iter().take(n_master).enumerate() {
-                let y = screen_y + (i as i16 * (master_height + gap));
-                let h = if i == n_master - 1 {
-                    screen_height - (i as i16 * (master_height + gap))
-                } else {
-                    master_height
-                };
-
-                self.configure_client(client, screen_x, y, master_width, h)?;
-            }
+        let area = self.tiled_area();
+        let config = &self.monitors.current().workspaces.current().layout_config;
+        let layout = layout_by_name(&config.current).unwrap_or(LAYOUTS[0]);
+        let tiles = layout.arrange(area, &clients, config);
+
+        for (&client, tile) in clients.iter().zip(tiles) {
+            self.configure_client(
+                client,
+                tile.x,
+                tile.y,
+                tile.width as i16,
+                tile.height as i16,
+            )?;
         }
 
-        // Stack
-        if n_stack > 0 {
-            let stack_x = screen_x + master_width + gap;
-            let stack_height = (screen_height - (gap * (n_stack as i16 - 1))) / n_stack as i16;
-
-            for (i, &client) in clients.iter().skip(n_master).enumerate() {
-                let y = screen_y + (i as i16 * (stack_height + gap));
-                let h = if i == n_stack - 1 {
-                    screen_height - (i as i16 * (stack_height + gap))
-                } else {
-                    stack_height
-                };
-
-                self.configure_client(client, stack_x, y, stack_width, h)?;
-            }
+        // tiles may overlap, as in monocle, so the focused one goes on top
+        match self.focused_client() {
+            Some(focused) if clients.contains(&focused) => self.raise_client(focused)?,
+            _ => self.restack()?,
         }
 
+        self.conn.flush()?;
         Ok(())
     }
 
-    pub fn apply_monocle_layout(&mut self) -> Result<()> {
-        let monitor = self.monitors.current().clone();
-
-        self.monitors
-            .current_mut()
-            .workspaces
-            .current_mut()
-            .sync_clients();
-
+    /// Tiled clients of the current workspace in layout order
+    fn tiled_clients(&self) -> Vec<Window> {
         let workspace = self.monitors.current().workspaces.current();
-        let padding = workspace.layout_config.screen_padding;
 
-        let x = monitor.x + padding;
-        let y = monitor.y + padding;
-        let width = monitor.width as i16 - padding * 2;
-        let height = monitor.height as i16 - padding * 2;
-
-        let clients: Vec<Window> = workspace
+        workspace
             .clients_order()
             .into_iter()
-            .filter(|w| {
-                if let Some(state) = workspace.clients.get(w) {
-                    !state.is_fullscreen
-                } else {
-                    false
-                }
-            })
-            .collect();
+            .filter(|w| workspace.clients.get(w).is_some_and(|s| s.is_tiled()))
+            .collect()
+    }
 
-        for client in clients {
-            self.configure_client(client, x, y, width, height)?;
-        }
+    /// Work area of the current monitor inside the workspace's screen padding
+    fn tiled_area(&self) -> Rect {
+        let monitor = self.monitors.current();
+        let padding = monitor.workspaces.current().layout_config.screen_padding;
 
-        if let Some(focused) = self.focused_client() {
-            self.conn.configure_window(
-                focused,
-                &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-            )?;
+        Rect {
+            x: monitor.workarea.x.saturating_add(padding),
+            y: monitor.workarea.y.saturating_add(padding),
+            width: (monitor.workarea.width as i16 - padding * 2).max(0) as u16,
+            height: (monitor.workarea.height as i16 - padding * 2).max(0) as u16,
         }
-
-        Ok(())
     }
 
     fn configure_client(
@@ -216,8 +148,28 @@ impl WindowManager {
         width: i16,
         height: i16,
     ) -> Result<()> {
-        let width = width.max(50);
-        let height = height.max(50);
+        let mut x = x;
+        let mut y = y;
+        let mut width = width.max(50);
+        let mut height = height.max(50);
+
+        let respect_increments = self
+            .monitors
+            .current()
+            .workspaces
+            .current()
+            .layout_config
+            .respect_increments;
+
+        // the part of the tile that does not fit a whole increment is split around it
+        if respect_increments && let Some(state) = self.clients().get(&window) {
+            let (w, h) = state.size_hints.fit_increments(width as u32, height as u32);
+
+            x += (width - w as i16) / 2;
+            y += (height - h as i16) / 2;
+            width = w as i16;
+            height = h as i16;
+        }
 
         self.conn.configure_window(
             window,
@@ -238,22 +190,80 @@ impl WindowManager {
         Ok(())
     }
 
+    /// Switch the current workspace to the layout after its current one in the
+    /// configured cycle
     pub fn next_layout(&mut self) -> Result<()> {
-        let workspace = self.monitors.current_mut().workspaces.current_mut();
+        let config = &self.monitors.current().workspaces.current().layout_config;
+
+        let next = config
+            .layouts
+            .iter()
+            .position(|name| *name == config.current)
+            .map_or(0, |i| i + 1);
 
-        workspace.layout_config.current = match workspace.layout_config.current {
-            LayoutType::MasterStack => LayoutType::Monocle,
-            LayoutType::Monocle => LayoutType::MasterStack,
+        let Some(name) = config.layouts.get(next).or(config.layouts.first()).cloned() else {
+            return Ok(());
         };
-        println!(
-            "Layout: {:?}",
-            self.monitors
-                .current()
-                .workspaces
-                .current()
-                .layout_config
-                .current
+
+        self.set_layout(&name)
+    }
+
+    /// Switch the current workspace to a registered layout
+    pub fn set_layout(&mut self, name: &str) -> Result<()> {
+        let Some(layout) = layout_by_name(name) else {
+            anyhow::bail!("unknown layout `{}`", name);
+        };
+
+        let workspace = self.monitors.current_mut().workspaces.current_mut();
+        workspace.layout_config.current = layout.name().to_string();
+        println!("Layout: {}", layout.name());
+
+        let monitor = self.monitors.current();
+        let workspace = monitor.workspaces.current();
+        self.emit(
+            "layout",
+            json!({
+                "monitor": monitor.id,
+                "workspace": workspace.id,
+                "layout": workspace.layout_config.current,
+            }),
         );
+
+        self.layout()
+    }
+
+    /// Tiled area of the current workspace and the x of the gap between master and
+    /// stack, when the master-stack layout shows both
+    pub fn master_split(&self) -> Option<(Rect, i16)> {
+        let config = &self.monitors.current().workspaces.current().layout_config;
+
+        if config.current != MasterStack::NAME {
+            return None;
+        }
+
+        let clients = self.tiled_clients();
+        if clients.len() < 2 || config.nmaster == 0 || config.nmaster >= clients.len() {
+            return None;
+        }
+
+        // the first stack tile starts a whole gap after the masters
+        let area = self.tiled_area();
+        let tiles = MasterStack.arrange(area, &clients, config);
+        let split = tiles[config.nmaster].x - (config.gap_size - config.gap_size / 2);
+
+        Some((area, split))
+    }
+
+    /// Set the master ratio of the current workspace, e.g. while dragging the split
+    pub fn set_master_ratio(&mut self, ratio: f32) -> Result<()> {
+        let workspace = self.monitors.current_mut().workspaces.current_mut();
+
+        let ratio = ratio.clamp(0.05, 0.95);
+        if workspace.layout_config.master_ratio == ratio {
+            return Ok(());
+        }
+
+        workspace.layout_config.master_ratio = ratio;
         self.layout()
     }
 
@@ -328,7 +338,7 @@ impl WindowManager {
             .into_iter()
             .filter(|w| {
                 if let Some(state) = workspace.clients.get(w) {
-                    !state.is_fullscreen
+                    state.is_tiled()
                 } else {
                     false
                 }
@@ -351,8 +361,7 @@ impl WindowManager {
 
             if workspace.focused_client == Some(first_client) && tiled_clients.len() > 1 {
                 workspace.focused_client = Some(tiled_clients[1]);
-                self.conn
-                    .set_input_focus(InputFocus::PARENT, tiled_clients[1], CURRENT_TIME)?;
+                self.give_input_focus(tiled_clients[1])?;
             }
         }
 
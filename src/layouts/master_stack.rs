@@ -0,0 +1,178 @@
+use x11rb::protocol::xproto::Window;
+
+use super::Layout;
+use crate::layout::{LayoutConfig, Rect};
+
+/// `nmaster` clients in a column on the left, the rest stacked on the right
+pub struct MasterStack;
+
+impl MasterStack {
+    pub const NAME: &'static str = "master-stack";
+}
+
+impl Layout for MasterStack {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn arrange(&self, area: Rect, clients: &[Window], config: &LayoutConfig) -> Vec<Rect> {
+        let n_clients = clients.len();
+
+        if n_clients <= 1 {
+            return vec![area; n_clients];
+        }
+
+        let gap = config.gap_size as i32;
+        let (x, width) = (area.x as i32, area.width as i32);
+
+        let n_master = config.nmaster.min(n_clients);
+        let n_stack = n_clients - n_master;
+
+        // a column left empty gives the other one the whole width
+        let (master_width, stack_x, stack_width) = if n_stack == 0 {
+            (width, x, 0)
+        } else if n_master == 0 {
+            (0, x, width)
+        } else {
+            let master_width = (width as f32 * config.master_ratio) as i32 - gap;
+            (
+                master_width,
+                x + master_width + gap,
+                width - master_width - gap,
+            )
+        };
+
+        let mut tiles = column(area, x, master_width, n_master, gap);
+        tiles.extend(column(area, stack_x, stack_width, n_stack, gap));
+
+        tiles
+    }
+}
+
+/// `count` tiles of equal height stacked in a column, the last one takes the rest
+fn column(area: Rect, x: i32, width: i32, count: usize, gap: i32) -> Vec<Rect> {
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let height = area.height as i32;
+    let tile_height = (height - gap * (count as i32 - 1)) / count as i32;
+
+    (0..count as i32)
+        .map(|i| {
+            let offset = i * (tile_height + gap);
+            let h = if i == count as i32 - 1 {
+                height - offset
+            } else {
+                tile_height
+            };
+
+            Rect {
+                x: x as i16,
+                y: (area.y as i32 + offset) as i16,
+                width: width.max(0) as u16,
+                height: h.max(0) as u16,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        x: 10,
+        y: 20,
+        width: 1000,
+        height: 600,
+    };
+
+    fn arrange(clients: usize, nmaster: usize, master_ratio: f32, gap_size: i16) -> Vec<Rect> {
+        let config = LayoutConfig {
+            nmaster,
+            master_ratio,
+            gap_size,
+            ..LayoutConfig::default()
+        };
+        let clients: Vec<Window> = (1..=clients as Window).collect();
+
+        MasterStack.arrange(AREA, &clients, &config)
+    }
+
+    fn rect(x: i16, y: i16, width: u16, height: u16) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn no_clients() {
+        assert!(arrange(0, 1, 0.5, 10).is_empty());
+    }
+
+    #[test]
+    fn single_client_fills_the_area() {
+        assert_eq!(arrange(1, 1, 0.5, 10), vec![AREA]);
+    }
+
+    #[test]
+    fn master_and_stack_split_by_ratio() {
+        assert_eq!(
+            arrange(2, 1, 0.5, 0),
+            vec![rect(10, 20, 500, 600), rect(510, 20, 500, 600)]
+        );
+    }
+
+    #[test]
+    fn gaps_between_columns_and_rows() {
+        assert_eq!(
+            arrange(4, 1, 0.5, 10),
+            vec![
+                rect(10, 20, 490, 600),
+                rect(510, 20, 500, 193),
+                rect(510, 223, 500, 193),
+                rect(510, 426, 500, 194),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_master_gives_the_stack_the_whole_width() {
+        assert_eq!(
+            arrange(2, 0, 0.5, 10),
+            vec![rect(10, 20, 1000, 295), rect(10, 325, 1000, 295)]
+        );
+    }
+
+    #[test]
+    fn more_masters_than_clients_leaves_no_stack() {
+        assert_eq!(
+            arrange(3, 5, 0.5, 0),
+            vec![
+                rect(10, 20, 1000, 200),
+                rect(10, 220, 1000, 200),
+                rect(10, 420, 1000, 200),
+            ]
+        );
+    }
+
+    #[test]
+    fn ratio_extremes_keep_both_columns_inside_the_area() {
+        for ratio in [0.05, 0.95] {
+            let tiles = arrange(2, 1, ratio, 10);
+            let (master, stack) = (tiles[0], tiles[1]);
+
+            assert!(master.width > 0 && stack.width > 0);
+            assert_eq!(master.x, AREA.x);
+            assert_eq!(stack.x, master.x + master.width as i16 + 10);
+            assert_eq!(stack.x + stack.width as i16, AREA.x + AREA.width as i16);
+        }
+
+        assert_eq!(arrange(2, 1, 0.05, 10)[0].width, 40);
+        assert_eq!(arrange(2, 1, 0.95, 10)[1].width, 50);
+    }
+}
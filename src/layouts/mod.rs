@@ -0,0 +1,26 @@
+mod master_stack;
+mod monocle;
+
+use x11rb::protocol::xproto::Window;
+
+use crate::layout::{LayoutConfig, Rect};
+
+pub use master_stack::MasterStack;
+pub use monocle::Monocle;
+
+/// A tiling layout, a pure function from the tiled area and clients to tiles
+pub trait Layout {
+    /// Name used in the config, key bindings and IPC
+    fn name(&self) -> &'static str;
+
+    /// One rectangle per client in `clients`, in the same order, inside `area`
+    fn arrange(&self, area: Rect, clients: &[Window], config: &LayoutConfig) -> Vec<Rect>;
+}
+
+/// Every layout dxwm knows, a new layout only has to be added here
+pub const LAYOUTS: &[&dyn Layout] = &[&MasterStack, &Monocle];
+
+/// Find a registered layout by name
+pub fn layout_by_name(name: &str) -> Option<&'static dyn Layout> {
+    LAYOUTS.iter().copied().find(|layout| layout.name() == name)
+}
@@ -0,0 +1,54 @@
+use x11rb::protocol::xproto::Window;
+
+use super::Layout;
+use crate::layout::{LayoutConfig, Rect};
+
+/// Every client takes the whole area, the focused one is raised on top
+pub struct Monocle;
+
+impl Monocle {
+    pub const NAME: &'static str = "monocle";
+}
+
+impl Layout for Monocle {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn arrange(&self, area: Rect, clients: &[Window], _config: &LayoutConfig) -> Vec<Rect> {
+        vec![area; clients.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        x: 10,
+        y: 20,
+        width: 1000,
+        height: 600,
+    };
+
+    #[test]
+    fn every_client_fills_the_area() {
+        let config = LayoutConfig::default();
+
+        assert!(Monocle.arrange(AREA, &[], &config).is_empty());
+        assert_eq!(Monocle.arrange(AREA, &[1], &config), vec![AREA]);
+        assert_eq!(Monocle.arrange(AREA, &[1, 2, 3], &config), vec![AREA; 3]);
+    }
+
+    #[test]
+    fn ignores_gaps_and_masters() {
+        let config = LayoutConfig {
+            nmaster: 2,
+            master_ratio: 0.95,
+            gap_size: 40,
+            ..LayoutConfig::default()
+        };
+
+        assert_eq!(Monocle.arrange(AREA, &[1, 2], &config), vec![AREA; 2]);
+    }
+}
@@ -3,10 +3,21 @@ mod atoms;
 mod banish;
 mod clients;
 mod config;
+mod docks;
+mod ewmh;
+mod floating;
+mod hints;
+mod ipc;
 mod keybindings;
 mod keyboard;
+mod keysyms;
 mod layout;
+mod layouts;
 mod monitors;
+mod mouse;
+mod outline;
+mod snap;
+mod stacking;
 mod utils;
 mod wm;
 mod workspaces;
@@ -17,6 +28,7 @@ fn main() -> Result<()> {
     let mut wm = wm::WindowManager::new()?;
 
     wm.setup_keybindings()?;
+    wm.setup_mouse_bindings()?;
 
     wm.run()?;
 
@@ -1,13 +1,18 @@
+use std::collections::HashMap;
+
+use crate::clients::ClientState;
+use crate::layout::{LayoutConfig, Rect};
 use crate::workspaces::Workspace;
 use crate::{wm::WindowManager, workspaces::WorkspaceManager};
 use anyhow::Result;
+use serde::Serialize;
+use serde_json::json;
 use x11rb::connection::Connection;
 use x11rb::protocol::randr::ConnectionExt as _;
 use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
-use x11rb::CURRENT_TIME;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Monitor {
     pub id: usize,
     pub name: String,
@@ -16,6 +21,8 @@ pub struct Monitor {
     pub width: u16,
     pub height: u16,
     pub primary: bool,
+    /// Area left to tiled clients once dock struts are subtracted
+    pub workarea: Rect,
     pub workspaces: WorkspaceManager,
 }
 
@@ -23,23 +30,19 @@ impl Monitor {
     pub fn new(
         id: usize,
         name: String,
-        x: i16,
-        y: i16,
-        width: u16,
-        height: u16,
+        geometry: Rect,
         primary: bool,
-        num_workspaces: u8,
+        workspaces: WorkspaceManager,
     ) -> Self {
-        let workspaces = WorkspaceManager::new(num_workspaces);
-
         Self {
             id,
             name,
-            x,
-            y,
-            width,
-            height,
+            x: geometry.x,
+            y: geometry.y,
+            width: geometry.width,
+            height: geometry.height,
             primary,
+            workarea: geometry,
             workspaces,
         }
     }
@@ -65,15 +68,25 @@ impl Monitor {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MonitorManager {
     pub monitors: Vec<Monitor>,
     pub current_monitor: usize,
+    pub num_workspaces: u8,
+    pub layout_config: LayoutConfig,
+    /// Monitor and workspace id of every managed client
+    #[serde(skip)]
+    pub client_index: HashMap<Window, (usize, u8)>,
 }
 
 #[allow(dead_code)]
 impl MonitorManager {
-    pub fn detect(conn: &RustConnection, root: Window, num_workspaces: u8) -> Result<Self> {
+    pub fn detect(
+        conn: &RustConnection,
+        root: Window,
+        num_workspaces: u8,
+        layout_config: &LayoutConfig,
+    ) -> Result<Self> {
         let screens_resources = conn.randr_get_screen_resources(root)?.reply().unwrap();
 
         let mut monitors: Vec<Monitor> = Vec::new();
@@ -94,15 +107,19 @@ impl MonitorManager {
             let name = String::from_utf8_lossy(&output_info.name).to_string();
             let is_primary = output == primary;
 
+            let geometry = Rect {
+                x: crtc_info.x,
+                y: crtc_info.y,
+                width: crtc_info.width,
+                height: crtc_info.height,
+            };
+
             monitors.push(Monitor::new(
                 monitor_id,
                 name,
-                crtc_info.x,
-                crtc_info.y,
-                crtc_info.width,
-                crtc_info.height,
+                geometry,
                 is_primary,
-                num_workspaces,
+                WorkspaceManager::new(num_workspaces, layout_config),
             ));
 
             monitor_id += 1;
@@ -111,15 +128,19 @@ impl MonitorManager {
         if monitors.is_empty() {
             let geometry = conn.get_geometry(root)?.reply().unwrap();
 
+            let geometry = Rect {
+                x: 0,
+                y: 0,
+                width: geometry.width,
+                height: geometry.height,
+            };
+
             monitors.push(Monitor::new(
                 0,
                 "Default".to_string(),
-                0,
-                0,
-                geometry.width,
-                geometry.height,
+                geometry,
                 true,
-                num_workspaces,
+                WorkspaceManager::new(num_workspaces, layout_config),
             ));
         }
 
@@ -132,19 +153,25 @@ impl MonitorManager {
         Ok(Self {
             monitors,
             current_monitor: 0,
+            num_workspaces,
+            layout_config: layout_config.clone(),
+            client_index: HashMap::new(),
         })
     }
 
     pub fn refresh(&mut self, conn: &RustConnection, root: Window) -> Result<Vec<MonitorChange>> {
         let old_monitors = self.monitors.clone();
-        let num_workspaces = old_monitors
-            .first()
-            .map(|m| m.workspaces.workspaces.len() as u8)
-            .unwrap_or(9);
 
-        let new_manager = Self::detect(&conn, root, num_workspaces)?;
+        let mut new_manager = Self::detect(&conn, root, self.num_workspaces, &self.layout_config)?;
         let mut changes = Vec::new();
 
+        // monitors that are still connected keep their workspaces and clients
+        for monitor in &mut new_manager.monitors {
+            if let Some(old_monitor) = old_monitors.iter().find(|m| m.name == monitor.name) {
+                monitor.workspaces = old_monitor.workspaces.clone();
+            }
+        }
+
         for old_monitor in &old_monitors {
             if !new_manager
                 .monitors
@@ -172,19 +199,39 @@ impl MonitorManager {
             })
             .collect();
 
-        if let Some(primary_idx) = new_manager.monitors.iter().position(|m| m.primary) {
-            for old_id in removed_ids {
-                if old_monitors.get(old_id).is_some() {
-                    println!(
-                        "Migrating windows from removed monitor {} to primary monitor",
-                        old_id
-                    );
-
-                    changes.push(MonitorChange::MigrateWindows {
-                        from: old_id,
-                        to: primary_idx,
-                    });
+        // detect always finds at least one monitor, the first stands in when
+        // RandR reports no primary output
+        let primary_idx = new_manager
+            .monitors
+            .iter()
+            .position(|m| m.primary)
+            .unwrap_or(0);
+
+        for old_id in removed_ids {
+            if old_monitors.get(old_id).is_some() {
+                println!(
+                    "Migrating windows from removed monitor {} to primary monitor",
+                    old_id
+                );
+
+                for workspace in &old_monitors[old_id].workspaces.workspaces {
+                    let target = new_manager.monitors[primary_idx]
+                        .workspaces
+                        .get_mut(workspace.id);
+
+                    if let Some(target) = target {
+                        for &window in &workspace.clients_order {
+                            if let Some(state) = workspace.clients.get(&window) {
+                                target.add_client(window, state.clone());
+                            }
+                        }
+                    }
                 }
+
+                changes.push(MonitorChange::MigrateWindows {
+                    from: old_id,
+                    to: primary_idx,
+                });
             }
         }
 
@@ -192,10 +239,79 @@ impl MonitorManager {
         self.current_monitor = self
             .current_monitor
             .min(self.monitors.len().saturating_sub(1));
+        self.rebuild_index();
 
         Ok(changes)
     }
 
+    /// Find the monitor and workspace id a client lives on
+    pub fn locate(&self, window: Window) -> Option<(usize, u8)> {
+        self.client_index.get(&window).copied()
+    }
+
+    /// Look up a client's state wherever it lives
+    pub fn client(&self, window: Window) -> Option<&ClientState> {
+        let (monitor, workspace) = self.locate(window)?;
+        self.monitors[monitor]
+            .workspaces
+            .get(workspace)?
+            .clients
+            .get(&window)
+    }
+
+    /// Look up a client's state wherever it lives (mutable)
+    pub fn client_mut(&mut self, window: Window) -> Option<&mut ClientState> {
+        let (monitor, workspace) = self.locate(window)?;
+        self.monitors[monitor]
+            .workspaces
+            .get_mut(workspace)?
+            .clients
+            .get_mut(&window)
+    }
+
+    /// Add a client to a workspace of any monitor
+    pub fn add_client(
+        &mut self,
+        monitor: usize,
+        workspace: u8,
+        window: Window,
+        state: ClientState,
+    ) -> bool {
+        let Some(ws) = self
+            .monitors
+            .get_mut(monitor)
+            .and_then(|m| m.workspaces.get_mut(workspace))
+        else {
+            return false;
+        };
+
+        ws.add_client(window, state);
+        self.client_index.insert(window, (monitor, workspace));
+        true
+    }
+
+    /// Remove a client from whichever workspace holds it
+    pub fn remove_client(&mut self, window: Window) -> Option<ClientState> {
+        let (monitor, workspace) = self.client_index.remove(&window)?;
+
+        self.monitors[monitor]
+            .workspaces
+            .get_mut(workspace)?
+            .remove_client(window)
+    }
+
+    fn rebuild_index(&mut self) {
+        self.client_index.clear();
+
+        for monitor in &self.monitors {
+            for workspace in &monitor.workspaces.workspaces {
+                for &window in workspace.clients.keys() {
+                    self.client_index.insert(window, (monitor.id, workspace.id));
+                }
+            }
+        }
+    }
+
     pub fn current(&self) -> &Monitor {
         &self.monitors[self.current_monitor]
     }
@@ -221,14 +337,18 @@ impl MonitorManager {
         }
     }
 
-    // pub fn find_monitor_for_window(&self, x: i16, y: i16, width: u16, height: u16) -> usize {
-    //     for monitor in &self.monitors {
-    //         if monitor.contains_window(x, y, width, height) {
-    //             return monitor.id;
-    //         }
-    //     }
-    //     self.current_monitor
-    // }
+    /// Monitor under a root window point, the current one when it is off screen
+    pub fn monitor_at(&self, x: i16, y: i16) -> usize {
+        self.monitors
+            .iter()
+            .position(|m| {
+                (x as i32) >= m.x as i32
+                    && (x as i32) < m.x as i32 + m.width as i32
+                    && (y as i32) >= m.y as i32
+                    && (y as i32) < m.y as i32 + m.height as i32
+            })
+            .unwrap_or(self.current_monitor)
+    }
 
     pub fn count(&self) -> usize {
         self.monitors.len()
@@ -255,22 +375,55 @@ impl WindowManager {
                 MonitorChange::Added(id) => {
                     println!("Monitor {} added", id);
                     self.draw_alert(format!("Monitor added: {}", id))?;
+                    self.emit("monitor", json!({ "change": "added", "id": id }));
                 }
                 MonitorChange::Removed(id) => {
                     println!("Monitor {} removed", id);
                     self.draw_alert(format!("Monitor removed: {}", id))?;
+                    self.emit("monitor", json!({ "change": "removed", "id": id }));
                 }
                 MonitorChange::MigrateWindows { from, to } => {
                     println!("Migrating windows from monitor {} to {}", from, to);
+                    self.sync_monitor_visibility(to)?;
+                    self.emit(
+                        "monitor",
+                        json!({ "change": "migrated", "from": from, "to": to }),
+                    );
                 }
             }
         }
 
+        self.update_workarea()?;
         self.layout_all_monitors()?;
 
         Ok(())
     }
 
+    /// Map the clients of a monitor's current workspace and hide the others
+    fn sync_monitor_visibility(&mut self, monitor_id: usize) -> Result<()> {
+        let Some(monitor) = self.monitors.get(monitor_id) else {
+            return Ok(());
+        };
+
+        let current = monitor.workspaces.current_workspace;
+        let windows: Vec<(Window, bool)> = monitor
+            .workspaces
+            .workspaces
+            .iter()
+            .flat_map(|ws| ws.clients.keys().map(move |&w| (w, ws.id == current)))
+            .collect();
+
+        for (window, visible) in windows {
+            if visible {
+                self.show_client(window)?;
+            } else {
+                self.hide_client(window)?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn layout_all_monitors(&mut self) -> Result<()> {
         let num_monitors = self.monitors.count();
 
@@ -331,13 +484,17 @@ impl WindowManager {
 
         // Focus current window on active workspace
         if let Some(focused) = self.focused_client() {
-            self.conn
-                .set_input_focus(InputFocus::PARENT, focused, CURRENT_TIME)?;
+            self.give_input_focus(focused)?;
             self.update_client_borders()?;
         }
 
         let monitor = self.monitors.current();
+        let workspace = monitor.workspaces.current_workspace;
         self.draw_alert(format!("Monitor: {} ({})", monitor.id, monitor.name))?;
+        self.emit(
+            "workspace",
+            json!({ "monitor": monitor_id, "workspace": workspace }),
+        );
 
         self.conn.flush()?;
         Ok(())
@@ -373,31 +530,17 @@ impl WindowManager {
                 window, current_monitor_id, target_monitor_id
             );
 
-            let current_ws_index = self.monitors.current().current_workspace();
-
             let state = self
                 .monitors
-                .current_mut()
-                .current_workspace_mut()
                 .remove_client(window)
                 .ok_or_else(|| anyhow::anyhow!("Window not found"))?;
 
-            let target_ws_id = {
-                let target_monitor = self
-                    .monitors
-                    .monitors
-                    .get(target_monitor_id)
-                    .ok_or_else(|| anyhow::anyhow!("Invalid target monitor"))?;
+            let target_ws_id = self.monitors.monitors[target_monitor_id]
+                .current_workspace()
+                .id;
 
-                target_monitor.current_workspace().id
-            };
-
-            if let Some(ws) = self.monitors.monitors[target_monitor_id]
-                .workspaces
-                .get_mut(target_ws_id)
-            {
-                ws.add_client(window, state);
-            }
+            self.monitors
+                .add_client(target_monitor_id, target_ws_id, window, state);
 
             let _saved_monitor = self.monitors.current_monitor;
             self.monitors.switch_to(current_monitor_id);
@@ -406,8 +549,7 @@ impl WindowManager {
             self.monitors.switch_to(target_monitor_id);
             self.layout()?;
 
-            self.conn
-                .set_input_focus(InputFocus::PARENT, window, CURRENT_TIME)?;
+            self.give_input_focus(window)?;
             self.update_client_borders()?;
 
             let target_monitor = self.monitors.current();
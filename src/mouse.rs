@@ -0,0 +1,424 @@
+use anyhow::{Context, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::CURRENT_TIME;
+
+use crate::layout::Rect;
+use crate::outline::Outline;
+use crate::wm::WindowManager;
+
+/// Modifier held to drag windows around with the mouse
+pub const MOUSE_MODIFIER: ModMask = ModMask::M4;
+
+/// Extra pixels on each side of the master/stack gap that still grab the split
+const SPLIT_GRAB_MARGIN: i16 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DragKind {
+    Move,
+    Resize,
+    /// A tile carried onto another tile to trade places
+    Swap,
+    /// The gap between master and stack, the window is the root
+    MasterRatio,
+}
+
+/// A pointer drag in progress
+#[derive(Debug, Clone, Copy)]
+pub struct Drag {
+    pub kind: DragKind,
+    pub window: Window,
+    /// Pointer position when the button went down
+    pub start_x: i16,
+    pub start_y: i16,
+    /// Client geometry when the button went down, the tiled area for the split
+    pub geometry: Rect,
+    /// Latest pointer position not applied yet, motion is coalesced per event batch
+    pub pending: Option<(i16, i16)>,
+    /// Drop target preview, created the first time there is one to show
+    pub outline: Option<Outline>,
+}
+
+impl WindowManager {
+    /// Grab Super+Button1 and Super+Button3 on the root for moving and resizing
+    pub fn setup_mouse_bindings(&self) -> Result<()> {
+        self.conn
+            .ungrab_button(ButtonIndex::ANY, self.root, ModMask::ANY)?;
+
+        let event_mask =
+            EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE | EventMask::BUTTON_MOTION;
+
+        for button in [ButtonIndex::M1, ButtonIndex::M3] {
+            // with and without Shift, whatever the state of Numlock and CapsLock
+            let modifiers = [MOUSE_MODIFIER, MOUSE_MODIFIER | ModMask::SHIFT]
+                .into_iter()
+                .flat_map(|modifiers| {
+                    [
+                        ModMask::default(),
+                        ModMask::M2,
+                        ModMask::LOCK,
+                        ModMask::LOCK | ModMask::M2,
+                    ]
+                    .map(|locks| modifiers | locks)
+                });
+
+            for modifiers in modifiers {
+                self.conn
+                    .grab_button(
+                        false,
+                        self.root,
+                        event_mask,
+                        GrabMode::ASYNC,
+                        GrabMode::ASYNC,
+                        x11rb::NONE,
+                        x11rb::NONE,
+                        button,
+                        modifiers,
+                    )?
+                    .check()
+                    .context("Failed to grab mouse button")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Start dragging the client under the pointer, or the master/stack split
+    /// when the root itself was clicked
+    pub fn handle_button_press(&mut self, e: ButtonPressEvent) -> Result<()> {
+        if self.drag.is_some() {
+            return Ok(());
+        }
+
+        if e.child == x11rb::NONE && e.detail == 1 {
+            return self.start_split_drag(e);
+        }
+
+        let window = e.child;
+        let Some(state) = self.find_client(window) else {
+            return Ok(());
+        };
+
+        if state.is_fullscreen {
+            return Ok(());
+        }
+
+        // Super+Button1 floats and moves a tile, Super+Shift+Button1 carries it
+        // onto another tile instead
+        let kind = match e.detail {
+            1 if state.is_tiled() && e.state.contains(KeyButMask::SHIFT) => DragKind::Swap,
+            1 => DragKind::Move,
+            3 => DragKind::Resize,
+            _ => return Ok(()),
+        };
+
+        // the layout below runs on the current monitor
+        if let Some((monitor_id, _)) = self.locate_client(window) {
+            self.monitors.switch_to(monitor_id);
+        }
+
+        // moving or resizing a tile takes it out of the layout where it stands
+        if kind != DragKind::Swap {
+            self.set_floating(window, true)?;
+        }
+
+        let Some(state) = self.find_client(window) else {
+            return Ok(());
+        };
+
+        let geometry = Rect {
+            x: state.x,
+            y: state.y,
+            width: state.width,
+            height: state.height,
+        };
+
+        self.drag = Some(Drag {
+            kind,
+            window,
+            start_x: e.root_x,
+            start_y: e.root_y,
+            geometry,
+            pending: None,
+            outline: None,
+        });
+
+        self.set_focused_client(Some(window));
+        self.give_input_focus(window)?;
+        self.raise_client(window)?;
+        self.update_client_borders()?;
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Grab the gap between master and stack when the click landed on it
+    fn start_split_drag(&mut self, e: ButtonPressEvent) -> Result<()> {
+        let monitor_id = self.monitors.monitor_at(e.root_x, e.root_y);
+        let saved_monitor = self.monitors.current_monitor;
+        self.monitors.switch_to(monitor_id);
+
+        let reach = self
+            .monitors
+            .current()
+            .workspaces
+            .current()
+            .layout_config
+            .gap_size
+            / 2
+            + SPLIT_GRAB_MARGIN;
+
+        let on_split = self.master_split().filter(|&(area, split)| {
+            (e.root_x - split).abs() <= reach
+                && e.root_y >= area.y
+                && (e.root_y as i32) < area.y as i32 + area.height as i32
+        });
+
+        let Some((area, _)) = on_split else {
+            self.monitors.switch_to(saved_monitor);
+            return Ok(());
+        };
+
+        // the root only selects button presses, motion needs an active grab
+        self.conn
+            .grab_pointer(
+                false,
+                self.root,
+                EventMask::BUTTON_RELEASE | EventMask::BUTTON_MOTION,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+                x11rb::NONE,
+                x11rb::NONE,
+                CURRENT_TIME,
+            )?
+            .reply()?;
+
+        self.drag = Some(Drag {
+            kind: DragKind::MasterRatio,
+            window: self.root,
+            start_x: e.root_x,
+            start_y: e.root_y,
+            geometry: area,
+            pending: None,
+            outline: None,
+        });
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Remember where the pointer went, `apply_drag` catches up once per batch
+    pub fn handle_motion_notify(&mut self, e: MotionNotifyEvent) {
+        if let Some(drag) = &mut self.drag {
+            drag.pending = Some((e.root_x, e.root_y));
+        }
+    }
+
+    /// Finish the drag, handing the client to the monitor or tile it was dropped on
+    pub fn handle_button_release(&mut self, e: ButtonReleaseEvent) -> Result<()> {
+        if let Some(drag) = &mut self.drag {
+            drag.pending = Some((e.root_x, e.root_y));
+        }
+        self.apply_drag()?;
+
+        let Some(drag) = self.drag.take() else {
+            return Ok(());
+        };
+
+        if let Some(outline) = drag.outline {
+            self.destroy_outline(outline)?;
+        }
+
+        match drag.kind {
+            DragKind::MasterRatio => {
+                self.conn.ungrab_pointer(CURRENT_TIME)?;
+            }
+            DragKind::Swap => {
+                self.drop_tile(drag.window, e.root_x, e.root_y)?;
+            }
+            DragKind::Move | DragKind::Resize => {
+                if drag.kind == DragKind::Move
+                    && let Some(rect) = self.edge_tile_target(e.root_x, e.root_y)
+                {
+                    let border = self.border_width * 2;
+                    self.move_resize_client(
+                        drag.window,
+                        rect.x,
+                        rect.y,
+                        (rect.width as u32).saturating_sub(border),
+                        (rect.height as u32).saturating_sub(border),
+                    )?;
+                }
+
+                let Some(state) = self.find_client(drag.window) else {
+                    return Ok(());
+                };
+
+                let center_x = state.x as i32 + state.width as i32 / 2;
+                let center_y = state.y as i32 + state.height as i32 / 2;
+                let monitor_id = self.monitors.monitor_at(center_x as i16, center_y as i16);
+
+                self.send_to_monitor(drag.window, monitor_id)?;
+            }
+        }
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Swap a dragged tile with the tile under the pointer, or hand it over to
+    /// the monitor it was dropped on
+    fn drop_tile(&mut self, window: Window, x: i16, y: i16) -> Result<()> {
+        let monitor_id = self.monitors.monitor_at(x, y);
+
+        if monitor_id != self.monitors.current_monitor {
+            return self.send_to_monitor(window, monitor_id);
+        }
+
+        let Some(target) = self.tile_at(x, y).filter(|&target| target != window) else {
+            return Ok(());
+        };
+
+        self.monitors
+            .current_mut()
+            .workspaces
+            .current_mut()
+            .swap_clients(window, target);
+
+        println!("Swapped client {} ↔ {}", window, target);
+
+        self.layout()
+    }
+
+    /// Move a client to the current workspace of another monitor and follow it
+    fn send_to_monitor(&mut self, window: Window, monitor_id: usize) -> Result<()> {
+        if monitor_id == self.monitors.current_monitor {
+            return Ok(());
+        }
+
+        let workspace_id = self.monitors.monitors[monitor_id]
+            .workspaces
+            .current_workspace;
+
+        if self.move_client(window, monitor_id, workspace_id)? {
+            self.monitors.switch_to(monitor_id);
+            self.set_focused_client(Some(window));
+            self.give_input_focus(window)?;
+            self.raise_client(window)?;
+            self.update_client_borders()?;
+        }
+
+        Ok(())
+    }
+
+    /// Tiled client of the current workspace under a root window point
+    fn tile_at(&self, x: i16, y: i16) -> Option<Window> {
+        let (x, y) = (x as i32, y as i32);
+        let border = self.border_width as i32 * 2;
+
+        self.clients()
+            .iter()
+            .find(|(_, state)| {
+                state.is_tiled()
+                    && x >= state.x as i32
+                    && x < state.x as i32 + state.width as i32 + border
+                    && y >= state.y as i32
+                    && y < state.y as i32 + state.height as i32 + border
+            })
+            .map(|(&window, _)| window)
+    }
+
+    /// Catch the dragged client or split up with the latest pointer position
+    pub fn apply_drag(&mut self) -> Result<()> {
+        let Some(drag) = &mut self.drag else {
+            return Ok(());
+        };
+
+        let Some((x, y)) = drag.pending.take() else {
+            return Ok(());
+        };
+        let drag = *drag;
+
+        let dx = x as i32 - drag.start_x as i32;
+        let dy = y as i32 - drag.start_y as i32;
+        let geometry = drag.geometry;
+
+        match drag.kind {
+            DragKind::Move => {
+                let (window_x, window_y) = self.snap_position(
+                    drag.window,
+                    (geometry.x as i32 + dx) as i16,
+                    (geometry.y as i32 + dy) as i16,
+                    geometry.width,
+                    geometry.height,
+                );
+
+                self.move_resize_client(
+                    drag.window,
+                    window_x,
+                    window_y,
+                    geometry.width as u32,
+                    geometry.height as u32,
+                )?;
+
+                let target = self.edge_tile_target(x, y);
+                self.show_drag_outline(target)
+            }
+            DragKind::Resize => {
+                let (width, height) = self.snap_size(
+                    drag.window,
+                    geometry.x,
+                    geometry.y,
+                    (geometry.width as i32 + dx).max(1) as u32,
+                    (geometry.height as i32 + dy).max(1) as u32,
+                );
+
+                self.move_resize_client(drag.window, geometry.x, geometry.y, width, height)
+            }
+            DragKind::Swap => {
+                let border = self.border_width as u16 * 2;
+                let target = self
+                    .tile_at(x, y)
+                    .and_then(|window| self.find_client(window))
+                    .map(|state| Rect {
+                        x: state.x,
+                        y: state.y,
+                        width: state.width + border,
+                        height: state.height + border,
+                    });
+
+                self.show_drag_outline(target)
+            }
+            DragKind::MasterRatio => {
+                // inverse of the split position in master_split
+                let gap = self
+                    .monitors
+                    .current()
+                    .workspaces
+                    .current()
+                    .layout_config
+                    .gap_size;
+                let offset = x as i32 - geometry.x as i32 + (gap - gap / 2) as i32;
+
+                self.set_master_ratio(offset as f32 / geometry.width.max(1) as f32)
+            }
+        }
+    }
+
+    /// Preview where the drag would drop, `None` hides the preview
+    fn show_drag_outline(&mut self, rect: Option<Rect>) -> Result<()> {
+        let outline = match self.drag.and_then(|drag| drag.outline) {
+            Some(outline) => outline,
+            None if rect.is_none() => return Ok(()),
+            None => {
+                let outline = self.create_outline()?;
+                if let Some(drag) = &mut self.drag {
+                    drag.outline = Some(outline);
+                }
+                outline
+            }
+        };
+
+        self.show_outline(&outline, rect)
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::COPY_DEPTH_FROM_PARENT;
+
+use crate::layout::Rect;
+use crate::wm::WindowManager;
+
+/// Thickness of the drop target outline
+const OUTLINE_WIDTH: u16 = 3;
+
+/// A frame drawn with four thin override-redirect windows, so it shows
+/// through nothing and needs no compositor
+#[derive(Debug, Clone, Copy)]
+pub struct Outline {
+    windows: [Window; 4],
+}
+
+impl Outline {
+    pub fn windows(&self) -> [Window; 4] {
+        self.windows
+    }
+}
+
+impl WindowManager {
+    /// Create an outline, it stays unmapped until shown
+    pub fn create_outline(&mut self) -> Result<Outline> {
+        let mut windows = [x11rb::NONE; 4];
+
+        for window in &mut windows {
+            *window = self.conn.generate_id()?;
+
+            self.conn.create_window(
+                COPY_DEPTH_FROM_PARENT,
+                *window,
+                self.root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                WindowClass::INPUT_OUTPUT,
+                0,
+                &CreateWindowAux::new()
+                    .background_pixel(self.border_focused_color)
+                    .override_redirect(1),
+            )?;
+        }
+
+        Ok(Outline { windows })
+    }
+
+    /// Frame a rectangle with the outline, `None` hides it
+    pub fn show_outline(&mut self, outline: &Outline, rect: Option<Rect>) -> Result<()> {
+        let Some(rect) = rect else {
+            for &window in &outline.windows {
+                self.conn.unmap_window(window)?;
+            }
+            self.conn.flush()?;
+            return Ok(());
+        };
+
+        let (x, y) = (rect.x as i32, rect.y as i32);
+        let (width, height) = (rect.width.max(1) as u32, rect.height.max(1) as u32);
+        let line = OUTLINE_WIDTH as u32;
+
+        let sides = [
+            (x, y, width, line),
+            (x, y + height as i32 - line as i32, width, line),
+            (x, y, line, height),
+            (x + width as i32 - line as i32, y, line, height),
+        ];
+
+        for (&window, (x, y, width, height)) in outline.windows.iter().zip(sides) {
+            self.conn.configure_window(
+                window,
+                &ConfigureWindowAux::new()
+                    .x(x)
+                    .y(y)
+                    .width(width)
+                    .height(height)
+                    .stack_mode(StackMode::ABOVE),
+            )?;
+            self.conn.map_window(window)?;
+        }
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    pub fn destroy_outline(&mut self, outline: Outline) -> Result<()> {
+        for window in outline.windows {
+            self.conn.destroy_window(window)?;
+        }
+
+        self.conn.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use x11rb::protocol::xproto::Window;
+
+use crate::layout::Rect;
+use crate::wm::WindowManager;
+
+/// How close to a monitor edge the pointer has to be to offer an edge tile
+const EDGE_TRIGGER: i32 = 2;
+
+/// Fraction of a monitor edge at each end that offers a quarter instead of a half
+const CORNER_FRACTION: i32 = 6;
+
+/// Offset that puts the closest of `edges` onto the closest of `lines`, if any
+/// pair is within `distance`
+fn snap_offset(edges: &[i32], lines: &[i32], distance: i32) -> i32 {
+    let mut best = 0;
+    let mut best_distance = distance + 1;
+
+    for &edge in edges {
+        for &line in lines {
+            let offset = line - edge;
+            if offset.abs() < best_distance {
+                best = offset;
+                best_distance = offset.abs();
+            }
+        }
+    }
+
+    best
+}
+
+/// Whether two spans overlap or come within `slack` of each other
+fn spans_meet(start: i32, end: i32, other_start: i32, other_end: i32, slack: i32) -> bool {
+    start <= other_end + slack && other_start <= end + slack
+}
+
+impl WindowManager {
+    /// Snap a floating client moved to `x`, `y` onto nearby monitor, work area and
+    /// window edges
+    pub fn snap_position(
+        &self,
+        window: Window,
+        x: i16,
+        y: i16,
+        width: u16,
+        height: u16,
+    ) -> (i16, i16) {
+        let distance = self.config.floating.snap_distance as i32;
+        if distance == 0 {
+            return (x, y);
+        }
+
+        let outer = self.outer_rect(x, y, width, height);
+        let (vertical, horizontal) = self.snap_lines(window, outer, distance);
+
+        let (left, top) = (x as i32, y as i32);
+        let right = left + outer.width as i32;
+        let bottom = top + outer.height as i32;
+
+        let dx = snap_offset(&[left, right], &vertical, distance);
+        let dy = snap_offset(&[top, bottom], &horizontal, distance);
+
+        ((left + dx) as i16, (top + dy) as i16)
+    }
+
+    /// Snap the right and bottom edges of a floating client being resized
+    pub fn snap_size(&self, window: Window, x: i16, y: i16, width: u32, height: u32) -> (u32, u32) {
+        let distance = self.config.floating.snap_distance as i32;
+        if distance == 0 {
+            return (width, height);
+        }
+
+        let outer = self.outer_rect(x, y, width as u16, height as u16);
+        let (vertical, horizontal) = self.snap_lines(window, outer, distance);
+
+        let right = outer.x as i32 + outer.width as i32;
+        let bottom = outer.y as i32 + outer.height as i32;
+
+        let dw = snap_offset(&[right], &vertical, distance);
+        let dh = snap_offset(&[bottom], &horizontal, distance);
+
+        (
+            (width as i32 + dw).max(1) as u32,
+            (height as i32 + dh).max(1) as u32,
+        )
+    }
+
+    /// Half or quarter of the monitor whose edge the pointer is pushing against,
+    /// as an outer rectangle
+    pub fn edge_tile_target(&self, x: i16, y: i16) -> Option<Rect> {
+        if !self.config.floating.edge_tiling {
+            return None;
+        }
+
+        let monitor = &self.monitors.monitors[self.monitors.monitor_at(x, y)];
+        let (x, y) = (x as i32, y as i32);
+
+        let (left, top) = (monitor.x as i32, monitor.y as i32);
+        let right = left + monitor.width as i32 - 1;
+        let bottom = top + monitor.height as i32 - 1;
+
+        let corner_x = monitor.width as i32 / CORNER_FRACTION;
+        let corner_y = monitor.height as i32 / CORNER_FRACTION;
+
+        let at_left = x - left <= EDGE_TRIGGER;
+        let at_right = right - x <= EDGE_TRIGGER;
+        let at_top = y - top <= EDGE_TRIGGER;
+        let at_bottom = bottom - y <= EDGE_TRIGGER;
+
+        let near_left = x - left <= corner_x;
+        let near_right = right - x <= corner_x;
+        let near_top = y - top <= corner_y;
+        let near_bottom = bottom - y <= corner_y;
+
+        // columns and rows of a 2x2 grid, `None` spanning both
+        let (column, row) = if (at_left || at_right) && (near_top || near_bottom) {
+            (Some(at_right), Some(near_bottom))
+        } else if (at_top || at_bottom) && (near_left || near_right) {
+            (Some(near_right), Some(at_bottom))
+        } else if at_left || at_right {
+            (Some(at_right), None)
+        } else {
+            return None;
+        };
+
+        let padding = monitor.workspaces.current().layout_config.screen_padding as i32;
+        let area = monitor.workarea;
+
+        let split = |start: i32, length: i32, half: Option<bool>| match half {
+            None => (start + padding, length - padding * 2),
+            Some(second) => {
+                let size = (length - padding * 3) / 2;
+                let offset = if second { padding * 2 + size } else { padding };
+                (start + offset, size)
+            }
+        };
+
+        let (x, width) = split(area.x as i32, area.width as i32, column);
+        let (y, height) = split(area.y as i32, area.height as i32, row);
+
+        Some(Rect {
+            x: x as i16,
+            y: y as i16,
+            width: width.max(1) as u16,
+            height: height.max(1) as u16,
+        })
+    }
+
+    /// A client geometry grown by its borders
+    fn outer_rect(&self, x: i16, y: i16, width: u16, height: u16) -> Rect {
+        let border = self.border_width as u16 * 2;
+
+        Rect {
+            x,
+            y,
+            width: width + border,
+            height: height + border,
+        }
+    }
+
+    /// Vertical and horizontal lines a window at `outer` can snap to: the edges of
+    /// its monitor and work area, and of the windows beside it
+    fn snap_lines(&self, window: Window, outer: Rect, distance: i32) -> (Vec<i32>, Vec<i32>) {
+        let center_x = outer.x as i32 + outer.width as i32 / 2;
+        let center_y = outer.y as i32 + outer.height as i32 / 2;
+        let monitor =
+            &self.monitors.monitors[self.monitors.monitor_at(center_x as i16, center_y as i16)];
+
+        let geometry = Rect {
+            x: monitor.x,
+            y: monitor.y,
+            width: monitor.width,
+            height: monitor.height,
+        };
+
+        let mut vertical = Vec::new();
+        let mut horizontal = Vec::new();
+
+        for area in [geometry, monitor.workarea] {
+            vertical.extend([area.x as i32, area.x as i32 + area.width as i32]);
+            horizontal.extend([area.y as i32, area.y as i32 + area.height as i32]);
+        }
+
+        let (left, top) = (outer.x as i32, outer.y as i32);
+        let right = left + outer.width as i32;
+        let bottom = top + outer.height as i32;
+
+        for (&other, state) in self.clients() {
+            if other == window || state.is_fullscreen {
+                continue;
+            }
+
+            let other = self.outer_rect(state.x, state.y, state.width, state.height);
+            let other_left = other.x as i32;
+            let other_top = other.y as i32;
+            let other_right = other_left + other.width as i32;
+            let other_bottom = other_top + other.height as i32;
+
+            // only edges the window could actually line up against
+            if spans_meet(top, bottom, other_top, other_bottom, distance) {
+                vertical.extend([other_left, other_right]);
+            }
+            if spans_meet(left, right, other_left, other_right, distance) {
+                horizontal.extend([other_top, other_bottom]);
+            }
+        }
+
+        (vertical, horizontal)
+    }
+}
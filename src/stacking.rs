@@ -0,0 +1,178 @@
+use anyhow::Result;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+
+use crate::clients::ClientState;
+use crate::wm::WindowManager;
+
+/// Stacking layers from the bottom of the screen to the top
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    /// `_NET_WM_WINDOW_TYPE_DESKTOP` windows
+    Desktop,
+    Below,
+    Tiled,
+    Floating,
+    Above,
+    Dock,
+    Fullscreen,
+    /// Alerts and drag previews
+    Overlay,
+}
+
+impl Layer {
+    fn of(state: &ClientState) -> Self {
+        if state.is_fullscreen {
+            Layer::Fullscreen
+        } else if state.is_below {
+            Layer::Below
+        } else if state.is_above {
+            Layer::Above
+        } else if state.is_floating {
+            Layer::Floating
+        } else {
+            Layer::Tiled
+        }
+    }
+}
+
+impl WindowManager {
+    /// Layer of a managed client, transients are never put under their parent
+    pub fn client_layer(&self, window: Window) -> Option<Layer> {
+        let mut state = self.monitors.client(window)?;
+        let mut layer = Layer::of(state);
+        let mut seen = vec![window];
+
+        while let Some(parent) = state.transient_for {
+            if seen.contains(&parent) {
+                break;
+            }
+            seen.push(parent);
+
+            let Some(parent_state) = self.monitors.client(parent) else {
+                break;
+            };
+            state = parent_state;
+            layer = layer.max(Layer::of(state));
+        }
+
+        Some(layer)
+    }
+
+    /// Bring a client to the top of its layer together with its transients
+    pub fn raise_client(&mut self, window: Window) -> Result<()> {
+        let mut raised = Vec::new();
+        let mut pending = vec![window];
+
+        // parents go first, a visited list keeps transient cycles from looping
+        while let Some(window) = pending.pop() {
+            if raised.contains(&window) {
+                continue;
+            }
+            raised.push(window);
+
+            pending.extend(
+                self.clients()
+                    .iter()
+                    .filter(|(_, state)| state.transient_for == Some(window))
+                    .map(|(&w, _)| w),
+            );
+        }
+
+        self.stack.retain(|w| !raised.contains(w));
+        self.stack.extend(raised);
+
+        self.restack()
+    }
+
+    /// Stack every visible window by layer, in raise order within a layer, with a
+    /// single configure per window from the bottom up
+    pub fn restack(&mut self) -> Result<()> {
+        let mut windows: Vec<(Layer, usize, Window)> = Vec::new();
+
+        for &window in &self.desktop_windows {
+            windows.push((Layer::Desktop, 0, window));
+        }
+
+        for monitor in &self.monitors.monitors {
+            for &window in monitor.workspaces.current().clients.keys() {
+                let Some(layer) = self.client_layer(window) else {
+                    continue;
+                };
+
+                // clients never raised yet go on top of their layer
+                let rank = self
+                    .stack
+                    .iter()
+                    .position(|&w| w == window)
+                    .unwrap_or(usize::MAX);
+
+                windows.push((layer, rank, window));
+            }
+        }
+
+        for &window in self.docks.keys() {
+            windows.push((Layer::Dock, 0, window));
+        }
+
+        let outline = self.drag.and_then(|drag| drag.outline);
+        for window in outline.iter().flat_map(|outline| outline.windows()) {
+            windows.push((Layer::Overlay, 0, window));
+        }
+
+        for alert in &self.alerts {
+            windows.push((Layer::Overlay, 1, alert.window));
+        }
+
+        windows.sort_by_key(|&(layer, rank, _)| (layer, rank));
+
+        // chaining each window right above the previous one leaves windows dxwm
+        // does not stack, like menus, where they are
+        let mut previous = None;
+        for (_, _, window) in windows {
+            let aux = match previous {
+                Some(sibling) => ConfigureWindowAux::new()
+                    .sibling(sibling)
+                    .stack_mode(StackMode::ABOVE),
+                None => ConfigureWindowAux::new().stack_mode(StackMode::BELOW),
+            };
+
+            self.conn.configure_window(window, &aux)?;
+            previous = Some(window);
+        }
+
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// Apply a `_NET_WM_STATE` remove (0), add (1) or toggle (2) of ABOVE or BELOW
+    pub fn change_stack_state(&mut self, window: Window, above: bool, action: u32) -> Result<()> {
+        let Some(state) = self.monitors.client_mut(window) else {
+            return Ok(());
+        };
+
+        let current = if above {
+            state.is_above
+        } else {
+            state.is_below
+        };
+        let enable = match action {
+            0 => false,
+            1 => true,
+            2 => !current,
+            _ => return Ok(()),
+        };
+
+        // a window is never both above and below
+        if above {
+            state.is_above = enable;
+            state.is_below &= !enable;
+        } else {
+            state.is_below = enable;
+            state.is_above &= !enable;
+        }
+
+        self.update_net_wm_state(window)?;
+        self.restack()
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
@@ -6,13 +9,16 @@ use x11rb::protocol::randr::ConnectionExt as _;
 use x11rb::protocol::xproto::*;
 use x11rb::protocol::Event;
 use x11rb::rust_connection::RustConnection;
-use x11rb::CURRENT_TIME;
 
 use crate::alerts::Alert;
 use crate::atoms::Atoms;
-use crate::config::config::{BORDER_FOCUSED, BORDER_UNFOCUSED, BORDER_WIDTH};
-use crate::keybindings::KeyBindingManager;
+use crate::config::config::Config;
+use crate::docks::Strut;
+use crate::ewmh::EwmhState;
+use crate::ipc::IpcServer;
+use crate::keybindings::{KeyAction, KeyBindingManager};
 use crate::monitors::MonitorManager;
+use crate::mouse::Drag;
 use crate::utils::run_autostart;
 
 pub struct WindowManager {
@@ -23,9 +29,25 @@ pub struct WindowManager {
     pub border_focused_color: u32,
     pub border_unfocused_color: u32,
     pub keybindings: KeyBindingManager,
+    /// Key bindings added over IPC, kept across config reloads
+    pub ipc_bindings: Vec<(u32, ModMask, KeyAction)>,
     pub alerts: Vec<Alert>,
     pub monitors: MonitorManager,
     pub atoms: Atoms,
+    pub config: Config,
+    pub ipc: Option<IpcServer>,
+    pub ewmh: EwmhState,
+    pub docks: HashMap<Window, Strut>,
+    /// Unmaps dxwm caused itself and must not be taken as withdrawals
+    pub expected_unmaps: HashMap<Window, u32>,
+    /// Window being moved or resized with the mouse
+    pub drag: Option<Drag>,
+    /// Clients in the order they were managed, as `_NET_CLIENT_LIST` wants them
+    pub managed: Vec<Window>,
+    /// Clients in the order they were raised, the last one on top of its layer
+    pub stack: Vec<Window>,
+    /// `_NET_WM_WINDOW_TYPE_DESKTOP` windows, kept under everything else
+    pub desktop_windows: Vec<Window>,
 }
 
 impl WindowManager {
@@ -60,28 +82,84 @@ impl WindowManager {
         conn.flush()?;
 
         let atoms = Atoms::new(&conn)?;
-        let monitors = MonitorManager::detect(&conn, root, 9)?;
 
-        Ok(Self {
+        let (config, config_error) = match Config::load() {
+            Ok(config) => (config, None),
+            Err(err) => {
+                eprintln!("Error loading config, using defaults: {}", err);
+                (Config::default(), Some(err))
+            }
+        };
+
+        let monitors = MonitorManager::detect(&conn, root, config.workspaces, &config.layout)?;
+
+        let mut wm = Self {
             conn,
             screen_num,
             root,
             keybindings: KeyBindingManager::new(),
+            ipc_bindings: Vec::new(),
             alerts: Vec::new(),
             monitors,
-            border_width: BORDER_WIDTH,
-            border_focused_color: BORDER_FOCUSED,
-            border_unfocused_color: BORDER_UNFOCUSED,
+            border_width: config.appearance.border_width,
+            border_focused_color: config.appearance.border_focused,
+            border_unfocused_color: config.appearance.border_unfocused,
             atoms,
-        })
+            config,
+            ipc: None,
+            ewmh: EwmhState::default(),
+            docks: HashMap::new(),
+            expected_unmaps: HashMap::new(),
+            drag: None,
+            managed: Vec::new(),
+            stack: Vec::new(),
+            desktop_windows: Vec::new(),
+        };
+
+        if let Err(err) = wm.setup_ipc() {
+            eprintln!("IPC disabled: {}", err);
+        }
+
+        wm.adopt_existing_windows()?;
+        wm.setup_ewmh()?;
+
+        if let Some(err) = config_error {
+            wm.draw_alert(format!("[CFG] {}", err))?;
+        }
+
+        Ok(wm)
     }
 
     pub fn run(&mut self) -> Result<()> {
         run_autostart();
 
+        let reload_requested = Arc::new(AtomicBool::new(false));
+        signal_hook::flag::register(signal_hook::consts::SIGHUP, Arc::clone(&reload_requested))
+            .context("Failed to register SIGHUP handler")?;
+
         loop {
+            let mut changed = false;
+
             while let Some(event) = self.conn.poll_for_event()? {
                 self.handle_event(event)?;
+                changed = true;
+            }
+
+            if let Err(err) = self.apply_drag() {
+                eprintln!("Error dragging window: {}", err);
+            }
+
+            changed |= self.handle_ipc();
+
+            if reload_requested.swap(false, Ordering::Relaxed) {
+                match self.reload_config() {
+                    Ok(()) => changed = true,
+                    Err(err) => eprintln!("Error reloading config: {}", err),
+                }
+            }
+
+            if changed && let Err(err) = self.update_ewmh() {
+                eprintln!("Error updating EWMH properties: {}", err);
             }
 
             self.clear_old_alerts()?;
@@ -98,12 +176,17 @@ impl WindowManager {
                 }
             }
             Event::MapRequest(e) => {
-                if let Err(err) = self.manage_client(e) {
+                if let Err(err) = self.manage_client(e.window) {
                     eprintln!("Error managing client: {}", err);
                 }
             }
+            Event::ConfigureRequest(e) => {
+                if let Err(err) = self.handle_configure_request(e) {
+                    eprintln!("Error handling configure request: {}", err);
+                }
+            }
             Event::UnmapNotify(e) => {
-                if let Err(err) = self.unmanage_client(e.window) {
+                if let Err(err) = self.handle_unmap_notify(e) {
                     eprintln!("Error unmanaging client: {}", err);
                 }
             }
@@ -112,27 +195,25 @@ impl WindowManager {
                     eprintln!("Error on destroy notify: {}", err);
                 }
             }
+            Event::ButtonPress(e) => {
+                if let Err(err) = self.handle_button_press(e) {
+                    eprintln!("Error handling button press: {}", err);
+                }
+            }
+            Event::MotionNotify(e) => self.handle_motion_notify(e),
+            Event::ButtonRelease(e) => {
+                if let Err(err) = self.handle_button_release(e) {
+                    eprintln!("Error handling button release: {}", err);
+                }
+            }
+            Event::PropertyNotify(e) => {
+                if let Err(err) = self.handle_property_notify(e) {
+                    eprintln!("Error handling property change: {}", err);
+                }
+            }
             Event::EnterNotify(e) => {
-                if self.clients().contains_key(&e.event) {
-                    if self.focused_client() != Some(e.event) {
-                        // TODO: focus respective monitor
-                        self.set_focused_client(Some(e.event));
-
-                        if let Err(err) =
-                            self.conn
-                                .set_input_focus(InputFocus::PARENT, e.event, CURRENT_TIME)
-                        {
-                            eprintln!("Error setting focus: {}", err);
-                        }
-
-                        if let Err(err) = self.update_client_borders() {
-                            eprintln!("Error updating borders: {}", err);
-                        }
-
-                        if let Err(err) = self.conn.flush() {
-                            eprintln!("Error flushing: {}", err);
-                        }
-                    }
+                if let Err(err) = self.handle_enter_notify(e) {
+                    eprintln!("Error handling enter notify: {}", err);
                 }
             }
             Event::Expose(e) => {
@@ -145,16 +226,8 @@ impl WindowManager {
                 }
             }
             Event::ClientMessage(e) => {
-                if e.type_ == self.atoms.net_wm_state {
-                    let data = e.data.as_data32();
-
-                    let action = data[0];
-                    let state1 = data[1];
-                    let state2 = data[2];
-
-                    if let Err(err) = self.handle_state_request(e.window, action, state1, state2) {
-                        eprintln!("Error handling state request: {}", err);
-                    }
+                if let Err(err) = self.handle_client_message(e) {
+                    eprintln!("Error handling client message: {}", err);
                 }
             }
             Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_) => {
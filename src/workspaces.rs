@@ -1,17 +1,16 @@
 use anyhow::Result;
+use serde::Serialize;
+use serde_json::json;
 use std::collections::HashMap;
 use x11rb::connection::Connection;
-use x11rb::protocol::xproto::ConnectionExt;
-use x11rb::protocol::xproto::InputFocus;
 use x11rb::protocol::xproto::Window;
-use x11rb::CURRENT_TIME;
 
 use crate::clients::ClientState;
 use crate::layout::LayoutConfig;
 use crate::utils::dedup_preserve_order;
 use crate::wm::WindowManager;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Workspace {
     pub id: u8,
     pub name: String,
@@ -23,14 +22,14 @@ pub struct Workspace {
 
 impl Workspace {
     /// Create a new Workspace instance
-    pub fn new(id: u8, name: String) -> Self {
+    pub fn new(id: u8, name: String, layout_config: LayoutConfig) -> Self {
         Self {
             id,
             name,
             clients: HashMap::new(),
             clients_order: Vec::new(),
             focused_client: None,
-            layout_config: LayoutConfig::default(),
+            layout_config,
         }
     }
 
@@ -93,7 +92,7 @@ impl Workspace {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct WorkspaceManager {
     pub workspaces: Vec<Workspace>,
     pub current_workspace: u8,
@@ -102,11 +101,11 @@ pub struct WorkspaceManager {
 
 impl WorkspaceManager {
     /// Create a new WorkspaceManager instance
-    pub fn new(num_workspaces: u8) -> Self {
+    pub fn new(num_workspaces: u8, layout_config: &LayoutConfig) -> Self {
         let mut workspaces = Vec::new();
 
         for i in 1..=num_workspaces {
-            workspaces.push(Workspace::new(i, format!("{}", i)));
+            workspaces.push(Workspace::new(i, format!("{}", i), layout_config.clone()));
         }
 
         Self {
@@ -155,21 +154,6 @@ impl WorkspaceManager {
         }
     }
 
-    /// Move the current client to an specified workspace
-    pub fn move_client_to_workspace(&mut self, window: Window, target_workspace: u8) -> bool {
-        if target_workspace < 1 || target_workspace > self.workspaces.len() as u8 {
-            return false;
-        }
-
-        if let Some(state) = self.current_mut().remove_client(window) {
-            if let Some(target) = self.get_mut(target_workspace) {
-                target.add_client(window, state);
-                return true;
-            }
-        }
-        false
-    }
-
     /// Get the total number of workspaces
     pub fn count(&self) -> usize {
         self.workspaces.len()
@@ -191,8 +175,9 @@ impl WindowManager {
         self.monitors.current_mut().workspaces.last_workspace =
             self.monitors.current().workspaces.current_workspace;
 
-        for &window in self.clients().keys() {
-            self.conn.unmap_window(window)?;
+        let hidden: Vec<Window> = self.clients().keys().copied().collect();
+        for window in hidden {
+            self.hide_client(window)?;
         }
 
         self.monitors
@@ -200,16 +185,22 @@ impl WindowManager {
             .workspaces
             .switch_to(workspace_id);
 
-        for &window in self.clients().keys() {
-            self.conn.map_window(window)?;
+        let shown: Vec<Window> = self.clients().keys().copied().collect();
+        for window in shown {
+            self.show_client(window)?;
         }
 
         if let Some(focused) = self.focused_client() {
-            self.conn
-                .set_input_focus(InputFocus::PARENT, focused, CURRENT_TIME)?;
+            self.give_input_focus(focused)?;
         }
 
         self.layout()?;
+        self.update_client_borders()?;
+
+        self.emit(
+            "workspace",
+            json!({ "monitor": self.monitors.current_monitor, "workspace": workspace_id }),
+        );
 
         self.conn.flush()?;
         Ok(true)
@@ -232,12 +223,13 @@ impl WindowManager {
         if let Some(window) = self.focused_client() {
             println!("Moving window {} to workspace {}", window, workspace_id);
 
-            self.conn.unmap_window(window)?;
+            self.hide_client(window)?;
 
-            self.monitors
-                .current_mut()
-                .workspaces
-                .move_client_to_workspace(window, workspace_id);
+            let monitor_id = self.monitors.current_monitor;
+            if let Some(state) = self.monitors.remove_client(window) {
+                self.monitors
+                    .add_client(monitor_id, workspace_id, window, state);
+            }
 
             self.layout()?;
 
@@ -247,6 +239,57 @@ impl WindowManager {
         Ok(true)
     }
 
+    /// Move any managed client to a workspace, possibly on another monitor
+    pub fn move_client(
+        &mut self,
+        window: Window,
+        monitor_id: usize,
+        workspace_id: u8,
+    ) -> Result<bool> {
+        let Some(source) = self.locate_client(window) else {
+            return Ok(false);
+        };
+
+        let visible = match self.monitors.get(monitor_id) {
+            Some(monitor) if monitor.workspaces.get(workspace_id).is_some() => {
+                monitor.workspaces.current_workspace == workspace_id
+            }
+            _ => return Ok(false),
+        };
+
+        if source == (monitor_id, workspace_id) {
+            return Ok(false);
+        }
+
+        println!(
+            "Moving window {} to workspace {} on monitor {}",
+            window, workspace_id, monitor_id
+        );
+
+        let Some(state) = self.monitors.remove_client(window) else {
+            return Ok(false);
+        };
+
+        self.monitors
+            .add_client(monitor_id, workspace_id, window, state);
+
+        if visible {
+            self.show_client(window)?;
+        } else {
+            self.hide_client(window)?;
+        }
+
+        if let Some(focused) = self.focused_client() {
+            self.give_input_focus(focused)?;
+        }
+
+        self.layout_all_monitors()?;
+        self.update_client_borders()?;
+
+        self.conn.flush()?;
+        Ok(true)
+    }
+
     /// Change to the last visited workspace
     pub fn cycle_last_workspace(&mut self) -> Result<()> {
         let _ = self.switch_to_workspace(self.monitors.current().workspaces.last_workspace);